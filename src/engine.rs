@@ -1,54 +1,175 @@
-use crate::engine_eval::context::{EngineEvaluationContext, FeatureContext};
-use crate::engine_eval::result::{EvaluationResult, FlagResult, SegmentResult};
-use crate::engine_eval::segment_evaluator::is_context_in_segment;
+#[cfg(feature = "rayon")]
+use crate::engine_eval::compiled::CompiledContext;
+use crate::engine_eval::compiled::CompiledSegment;
+use crate::engine_eval::context::{
+    ContextView, EngineEvaluationContext, FeatureContext, FeatureValue, IdentityContext,
+    SegmentContext, SegmentSource,
+};
+use crate::engine_eval::options::{EvaluationOptions, StopServingFlagsBehaviour};
+use crate::engine_eval::result::{EvaluationResult, FlagResult, Reason, SegmentResult};
+use crate::engine_eval::segment_evaluator::is_context_in_keyed_segment;
+use crate::engine_eval::trace::{EvaluationTrace, FlagTrace, OverrideTrace, SegmentTrace};
 use crate::utils::hashing;
-use std::collections::HashMap;
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 
-/// Holds a feature context with its associated segment name for priority comparison
+/// Holds a feature context with its associated segment for priority comparison
 struct FeatureContextWithSegment {
     feature_context: FeatureContext,
     segment_name: String,
+    segment_key: String,
+    segment_source: SegmentSource,
+}
+
+/// Segment overrides of the segments the context is in
+#[derive(Default)]
+struct SegmentOverrides {
+    /// The selected override of each feature, by feature name.
+    feature_contexts: HashMap<String, FeatureContextWithSegment>,
+    /// Names of the features with an override outside its schedule.
+    inactive_features: HashSet<String>,
 }
 
 /// Helper to get priority or default
 fn get_priority_or_default(priority: Option<f64>) -> f64 {
-    priority.unwrap_or(f64::INFINITY) // Weakest possible priority
+    // NaN priorities are treated as missing, so they can never panic or win a comparison
+    priority.filter(|p| !p.is_nan()).unwrap_or(f64::INFINITY) // Weakest possible priority
 }
 
-/// Gets matching segments and their overrides
-fn get_matching_segments_and_overrides(
-    ec: &EngineEvaluationContext,
-) -> (
-    Vec<SegmentResult>,
-    HashMap<String, FeatureContextWithSegment>,
+/// Compares variants by priority (lower priority value = higher priority)
+pub(crate) fn compare_variant_priority(a: &FeatureValue, b: &FeatureValue) -> Ordering {
+    let pa = get_priority_or_default(a.priority);
+    let pb = get_priority_or_default(b.priority);
+    pa.total_cmp(&pb)
+}
+
+/// Gets the variants of a feature context sorted by priority, borrowing them when already sorted
+fn get_sorted_variants(feature_context: &FeatureContext) -> Cow<'_, [FeatureValue]> {
+    let variants = &feature_context.variants;
+    if variants.is_sorted_by(|a, b| compare_variant_priority(a, b).is_le()) {
+        return Cow::Borrowed(variants);
+    }
+
+    let mut sorted_variants = variants.clone();
+    sorted_variants.sort_by(compare_variant_priority);
+    Cow::Owned(sorted_variants)
+}
+
+/// Checks if the context is in a segment, recording the segment into `trace` when given
+fn is_context_in_traced_segment<'a>(
+    ec: &ContextView<'a>,
+    segment_key: &'a str,
+    segment_context: &SegmentContext,
+    compiled_segment: Option<&CompiledSegment>,
+    trace: Option<&mut EvaluationTrace>,
+) -> bool {
+    let Some(trace) = trace else {
+        return is_context_in_keyed_segment(
+            ec,
+            segment_key,
+            segment_context,
+            compiled_segment,
+            None,
+        );
+    };
+
+    let mut segment_trace = SegmentTrace {
+        key: segment_context.key.clone(),
+        name: segment_context.name.clone(),
+        matched: false,
+        rules: vec![],
+    };
+    segment_trace.matched = is_context_in_keyed_segment(
+        ec,
+        segment_key,
+        segment_context,
+        compiled_segment,
+        Some(&mut segment_trace.rules),
+    );
+    let matched = segment_trace.matched;
+    trace.segments.push(segment_trace);
+    matched
+}
+
+/// Records a segment override competing for a feature
+fn trace_override(
+    trace: &mut EvaluationTrace,
+    segment_context: &SegmentContext,
+    override_fc: &FeatureContext,
+    active: bool,
+    selected: bool,
 ) {
-    let mut segments = Vec::new();
-    let mut segment_feature_contexts: HashMap<String, FeatureContextWithSegment> = HashMap::new();
+    let flag_trace = trace
+        .flags
+        .entry(override_fc.name.clone())
+        .or_insert_with(|| FlagTrace {
+            feature_name: override_fc.name.clone(),
+            overrides: vec![],
+        });
 
-    // Sort segment keys for deterministic ordering
-    let mut segment_keys: Vec<_> = ec.segments.keys().collect();
-    segment_keys.sort();
+    if selected {
+        for previous in flag_trace.overrides.iter_mut() {
+            previous.selected = false;
+        }
+    }
+
+    flag_trace.overrides.push(OverrideTrace {
+        segment_key: segment_context.key.clone(),
+        segment_name: segment_context.name.clone(),
+        priority: override_fc.priority,
+        active,
+        selected,
+    });
+}
 
-    // Process segments in sorted order
-    for segment_key in segment_keys {
-        let segment_context = &ec.segments[segment_key];
+/// Gets matching segments and their overrides
+///
+/// Segments are expected in evaluation order, each paired with its compiled segment if available.
+fn get_matching_segments_and_overrides<'a>(
+    ec: &ContextView<'a>,
+    segment_contexts: impl Iterator<Item = (&'a str, &'a SegmentContext, Option<&'a CompiledSegment>)>,
+    options: &EvaluationOptions,
+    mut trace: Option<&mut EvaluationTrace>,
+) -> (Vec<SegmentResult>, SegmentOverrides) {
+    let mut segments = Vec::new();
+    let mut segment_overrides = SegmentOverrides::default();
 
-        if !is_context_in_segment(ec, segment_context) {
+    for (segment_key, segment_context, compiled_segment) in segment_contexts {
+        if !is_context_in_traced_segment(
+            ec,
+            segment_key,
+            segment_context,
+            compiled_segment,
+            trace.as_deref_mut(),
+        ) {
             continue;
         }
 
         // Add segment to results
-        segments.push(SegmentResult {
+        let segment_result = SegmentResult {
             name: segment_context.name.clone(),
             metadata: segment_context.metadata.clone(),
-        });
+        };
+        for observer in &options.observers {
+            observer.on_segment_matched(&segment_result);
+        }
+        segments.push(segment_result);
 
         // Process segment overrides
         for override_fc in &segment_context.overrides {
             let feature_name = &override_fc.name;
+            let active = is_feature_context_active(ec, override_fc);
 
             // Check if we should update the segment feature context
-            let should_update = if let Some(existing) = segment_feature_contexts.get(feature_name) {
+            let should_update = if !active {
+                segment_overrides
+                    .inactive_features
+                    .insert(feature_name.clone());
+                false
+            } else if let Some(existing) = segment_overrides.feature_contexts.get(feature_name) {
                 let existing_priority = get_priority_or_default(existing.feature_context.priority);
                 let override_priority = get_priority_or_default(override_fc.priority);
                 override_priority < existing_priority
@@ -56,77 +177,621 @@ fn get_matching_segments_and_overrides(
                 true
             };
 
+            if let Some(trace) = trace.as_deref_mut() {
+                trace_override(trace, segment_context, override_fc, active, should_update);
+            }
+
             if should_update {
-                segment_feature_contexts.insert(
+                segment_overrides.feature_contexts.insert(
                     feature_name.clone(),
                     FeatureContextWithSegment {
                         feature_context: override_fc.clone(),
                         segment_name: segment_context.name.clone(),
+                        segment_key: segment_context.key.clone(),
+                        segment_source: segment_context.metadata.source.clone(),
                     },
                 );
             }
         }
     }
 
-    (segments, segment_feature_contexts)
+    (segments, segment_overrides)
 }
 
 /// Gets flag results from feature contexts and segment overrides
 fn get_flag_results(
-    ec: &EngineEvaluationContext,
-    segment_feature_contexts: &HashMap<String, FeatureContextWithSegment>,
+    ec: &ContextView,
+    segment_overrides: &SegmentOverrides,
+    options: &EvaluationOptions,
 ) -> HashMap<String, FlagResult> {
     let mut flags = HashMap::new();
 
-    // Get identity key if identity exists
-    // If identity key is not provided, construct it from environment key and identifier
-    let identity_key: Option<String> = ec.identity.as_ref().map(|i| {
-        if i.key.is_empty() {
-            format!("{}_{}", ec.environment.key, i.identifier)
-        } else {
-            i.key.clone()
-        }
-    });
+    let identity_key = get_identity_key(ec);
 
     // Process all features
-    for feature_context in ec.features.values() {
-        // Check if we have a segment override for this feature
-        if let Some(segment_fc) = segment_feature_contexts.get(&feature_context.name) {
-            // Use segment override with multivariate evaluation
-            let fc = &segment_fc.feature_context;
-            let reason = format!("TARGETING_MATCH; segment={}", segment_fc.segment_name);
-            let flag_result =
-                get_flag_result_from_feature_context(fc, identity_key.as_ref(), reason);
-            flags.insert(feature_context.name.clone(), flag_result);
-        } else {
-            // Use default feature context
-            let flag_result = get_flag_result_from_feature_context(
+    for feature_context in ec.context.features.values() {
+        let static_flag_result = ec
+            .static_flag_results
+            .and_then(|flag_results| flag_results.get(&feature_context.name));
+        let flag_result = match static_flag_result {
+            Some(flag_result) => Some(flag_result.clone()),
+            None => get_flag_result_with_overrides(
+                ec,
                 feature_context,
+                segment_overrides,
                 identity_key.as_ref(),
-                "DEFAULT".to_string(),
-            );
+                options,
+            ),
+        };
+        if let Some(flag_result) = flag_result {
             flags.insert(feature_context.name.clone(), flag_result);
         }
     }
 
+    let features = &ec.context.features;
+    apply_prerequisites(features, features.keys().map(String::as_str), &mut flags);
+    if ec.context.environment.hide_disabled_flags {
+        flags.retain(|_, flag_result| flag_result.enabled);
+    }
+
+    for flag_result in flags.values() {
+        notify_flag_evaluated(ec, options, flag_result);
+    }
+
     flags
 }
 
+/// Gets the names of a feature and of every feature it depends on through prerequisites
+pub(crate) fn get_feature_and_prerequisites<'a>(
+    features: &'a HashMap<String, FeatureContext>,
+    feature_context: &'a FeatureContext,
+) -> HashSet<&'a str> {
+    let mut names = HashSet::from([feature_context.name.as_str()]);
+    let mut pending: Vec<&FeatureContext> = vec![feature_context];
+    while let Some(fc) = pending.pop() {
+        for prerequisite in &fc.prerequisites {
+            if names.insert(prerequisite.feature.as_str()) {
+                pending.extend(features.get(&prerequisite.feature));
+            }
+        }
+    }
+    names
+}
+
+/// Checks if a feature depends on itself through its prerequisites
+pub(crate) fn is_in_prerequisite_cycle(
+    features: &HashMap<String, FeatureContext>,
+    feature_name: &str,
+) -> bool {
+    let Some(feature_context) = features.get(feature_name) else {
+        return false;
+    };
+    let mut visited = HashSet::new();
+    let mut pending: Vec<&str> = feature_context
+        .prerequisites
+        .iter()
+        .map(|prerequisite| prerequisite.feature.as_str())
+        .collect();
+    while let Some(name) = pending.pop() {
+        if name == feature_name {
+            return true;
+        }
+        if visited.insert(name) {
+            if let Some(fc) = features.get(name) {
+                pending.extend(fc.prerequisites.iter().map(|p| p.feature.as_str()));
+            }
+        }
+    }
+    false
+}
+
+/// Disables the flags of the named features whose prerequisites are not met
+///
+/// Prerequisites are resolved before the features depending on them. Flags missing from
+/// `flags`, e.g. because they are hidden, are not met. Features that depend on themselves
+/// are disabled with `Reason::PrerequisiteCycle` instead of being resolved.
+fn apply_prerequisites<'a>(
+    features: &'a HashMap<String, FeatureContext>,
+    feature_names: impl Iterator<Item = &'a str>,
+    flags: &mut HashMap<String, FlagResult>,
+) {
+    let mut resolved = HashSet::new();
+    for feature_name in feature_names {
+        resolve_prerequisites(features, feature_name, flags, &mut resolved);
+    }
+}
+
+/// Disables the flag of a feature if its prerequisites are not met, resolving them first
+fn resolve_prerequisites<'a>(
+    features: &'a HashMap<String, FeatureContext>,
+    feature_name: &'a str,
+    flags: &mut HashMap<String, FlagResult>,
+    resolved: &mut HashSet<&'a str>,
+) {
+    let Some(feature_context) = features.get(feature_name) else {
+        return;
+    };
+    if feature_context.prerequisites.is_empty() || !resolved.insert(feature_name) {
+        return;
+    }
+
+    if is_in_prerequisite_cycle(features, feature_name) {
+        disable_flag(flags, feature_name, Reason::PrerequisiteCycle);
+        return;
+    }
+
+    for prerequisite in &feature_context.prerequisites {
+        resolve_prerequisites(features, &prerequisite.feature, flags, resolved);
+
+        let met = flags.get(&prerequisite.feature).is_some_and(|flag_result| {
+            flag_result.enabled
+                && prerequisite
+                    .value
+                    .as_ref()
+                    .is_none_or(|value| *value == flag_result.value)
+        });
+        if !met {
+            let reason = Reason::PrerequisiteNotMet {
+                feature: prerequisite.feature.clone(),
+            };
+            disable_flag(flags, feature_name, reason);
+            return;
+        }
+    }
+}
+
+/// Disables a flag, giving the reason it was disabled
+fn disable_flag(flags: &mut HashMap<String, FlagResult>, feature_name: &str, reason: Reason) {
+    if let Some(flag_result) = flags.get_mut(feature_name) {
+        flag_result.enabled = false;
+        flag_result.reason = reason;
+    }
+}
+
+/// Reads the clock of `options`, if it has one, as the time of the evaluation
+/// Notifies the observers of a flag result, skipping feature analytics observers
+/// unless the environment collects feature analytics
+fn notify_flag_evaluated(ec: &ContextView, options: &EvaluationOptions, flag_result: &FlagResult) {
+    let feature_analytics = ec.context.environment.feature_analytics;
+    for observer in &options.observers {
+        if feature_analytics || !observer.records_feature_analytics() {
+            observer.on_flag_evaluated(flag_result);
+        }
+    }
+}
+
+fn set_evaluation_time(ec: &ContextView, options: &EvaluationOptions) {
+    if let Some(clock) = options.clock {
+        // The time is only read once per evaluation
+        let _ = ec.now.set(clock.now());
+    }
+}
+
+/// Checks if a feature context is active at the time of the evaluation
+///
+/// The clock is only read for feature contexts that have a schedule.
+fn is_feature_context_active(ec: &ContextView, feature_context: &FeatureContext) -> bool {
+    !feature_context.is_scheduled() || feature_context.is_active_at(ec.now())
+}
+
+/// Gets the identity key used for percentage splits, if an identity exists
+fn get_identity_key(ec: &ContextView) -> Option<String> {
+    // If identity key is not provided, construct it from environment key and identifier
+    ec.identity.map(|i| {
+        if i.key.is_empty() {
+            format!("{}_{}", ec.context.environment.key, i.identifier)
+        } else {
+            i.key.clone()
+        }
+    })
+}
+
+/// Gets the flag result for a feature, using its segment override if there is one
+///
+/// Without an active override, a feature outside its schedule is disabled with
+/// `Reason::Scheduled`. A feature inside its schedule is served by default, with
+/// `Reason::Scheduled` if one of its overrides was left out for being outside its schedule.
+///
+/// Returns `None` if the flag ends up disabled and the environment hides disabled flags.
+/// Prerequisites are not checked, and observers are not notified of the flag.
+fn get_flag_result_with_overrides(
+    ec: &ContextView,
+    feature_context: &FeatureContext,
+    segment_overrides: &SegmentOverrides,
+    identity_key: Option<&String>,
+    options: &EvaluationOptions,
+) -> Option<FlagResult> {
+    // Check if we have a segment override for this feature
+    let (fc, reason) = if let Some(segment_fc) = segment_overrides
+        .feature_contexts
+        .get(&feature_context.name)
+    {
+        // Use segment override with multivariate evaluation
+        let reason = match segment_fc.segment_source {
+            SegmentSource::IdentityOverride => Reason::IdentityOverride,
+            SegmentSource::Api => Reason::TargetingMatch {
+                segment_name: segment_fc.segment_name.clone(),
+                segment_key: Some(segment_fc.segment_key.clone()),
+            },
+        };
+        (&segment_fc.feature_context, reason)
+    } else if !is_feature_context_active(ec, feature_context) {
+        if ec.context.environment.hide_disabled_flags {
+            return None;
+        }
+        return Some(FlagResult {
+            enabled: false,
+            name: feature_context.name.clone(),
+            value: feature_context.value.clone(),
+            reason: Reason::Scheduled,
+            metadata: feature_context.metadata.clone(),
+        });
+    } else if segment_overrides
+        .inactive_features
+        .contains(&feature_context.name)
+    {
+        // Use default feature context, as its overrides are outside their schedule
+        (feature_context, Reason::Scheduled)
+    } else {
+        // Use default feature context
+        (feature_context, Reason::Default)
+    };
+
+    if ec.context.environment.hide_disabled_flags && !fc.enabled {
+        return None;
+    }
+
+    Some(get_flag_result_from_feature_context(
+        fc,
+        identity_key,
+        reason,
+        options,
+    ))
+}
+
+/// Gets the flag result for a feature served with its environment default
+///
+/// Only gives the same result as evaluating the feature for features without variants or
+/// schedule that no segment overrides. Returns `None` if the flag is disabled and the
+/// environment hides disabled flags.
+pub(crate) fn get_default_flag_result(
+    ec: &EngineEvaluationContext,
+    feature_context: &FeatureContext,
+) -> Option<FlagResult> {
+    if ec.environment.hide_disabled_flags && !feature_context.enabled {
+        return None;
+    }
+
+    Some(get_flag_result_from_feature_context(
+        feature_context,
+        None,
+        Reason::Default,
+        &EvaluationOptions::default(),
+    ))
+}
+
+/// Gets the flag result for a feature of a context whose organisation stopped serving flags
+///
+/// Returns `None` unless `options` asks for disabled flags and the environment shows them.
+fn get_stopped_flag_result(
+    ec: &ContextView,
+    feature_context: &FeatureContext,
+    options: &EvaluationOptions,
+) -> Option<FlagResult> {
+    match options.stop_serving_flags {
+        StopServingFlagsBehaviour::Empty => None,
+        StopServingFlagsBehaviour::Disabled if ec.context.environment.hide_disabled_flags => None,
+        StopServingFlagsBehaviour::Disabled => {
+            let flag_result = FlagResult {
+                enabled: false,
+                name: feature_context.name.clone(),
+                value: feature_context.value.clone(),
+                reason: Reason::ServingStopped,
+                metadata: feature_context.metadata.clone(),
+            };
+
+            notify_flag_evaluated(ec, options, &flag_result);
+
+            Some(flag_result)
+        }
+    }
+}
+
+/// Checks if a segment overrides the named feature
+pub(crate) fn segment_overrides_feature(
+    segment_context: &SegmentContext,
+    feature_name: &str,
+) -> bool {
+    segment_context
+        .overrides
+        .iter()
+        .any(|override_fc| override_fc.name == feature_name)
+}
+
+/// Gets all segments of the context in evaluation order
+fn get_sorted_segment_contexts(
+    ec: &EngineEvaluationContext,
+) -> impl Iterator<Item = (&str, &SegmentContext, Option<&CompiledSegment>)> {
+    // Sort segment keys for deterministic ordering
+    let mut segment_keys: Vec<_> = ec.segments.keys().collect();
+    segment_keys.sort();
+
+    segment_keys
+        .into_iter()
+        .map(|segment_key| (segment_key.as_str(), &ec.segments[segment_key], None))
+}
+
 pub fn get_evaluation_result(ec: &EngineEvaluationContext) -> EvaluationResult {
+    get_evaluation_result_with_options(ec, &EvaluationOptions::default())
+}
+
+/// Evaluates the context, notifying the observers given in `options`
+pub fn get_evaluation_result_with_options(
+    ec: &EngineEvaluationContext,
+    options: &EvaluationOptions,
+) -> EvaluationResult {
+    get_evaluation_result_for_segments(
+        &ContextView::from(ec),
+        get_sorted_segment_contexts(ec),
+        options,
+        None,
+    )
+}
+
+/// Evaluates the context and explains how the result was reached
+///
+/// The trace lists every segment rule and condition visited, and the segment
+/// overrides competing for each feature.
+pub fn get_evaluation_result_with_trace(
+    ec: &EngineEvaluationContext,
+) -> (EvaluationResult, EvaluationTrace) {
+    get_evaluation_result_with_trace_with_options(ec, &EvaluationOptions::default())
+}
+
+/// Evaluates the context and explains how the result was reached, with the clock,
+/// observers and stop serving behaviour given in `options`
+pub fn get_evaluation_result_with_trace_with_options(
+    ec: &EngineEvaluationContext,
+    options: &EvaluationOptions,
+) -> (EvaluationResult, EvaluationTrace) {
+    let mut trace = EvaluationTrace::default();
+    let result = get_evaluation_result_for_segments(
+        &ContextView::from(ec),
+        get_sorted_segment_contexts(ec),
+        options,
+        Some(&mut trace),
+    );
+    (result, trace)
+}
+
+/// Evaluates the context for an identity kept apart from it
+///
+/// Gives the same result as evaluating a copy of the context with its identity replaced
+/// by `identity`, without copying the context.
+pub fn get_evaluation_result_for_identity(
+    ec: &EngineEvaluationContext,
+    identity: &IdentityContext,
+) -> EvaluationResult {
+    get_evaluation_result_for_identity_with_options(ec, identity, &EvaluationOptions::default())
+}
+
+/// Evaluates the context for an identity kept apart from it, notifying the observers
+/// given in `options`
+pub fn get_evaluation_result_for_identity_with_options(
+    ec: &EngineEvaluationContext,
+    identity: &IdentityContext,
+    options: &EvaluationOptions,
+) -> EvaluationResult {
+    get_evaluation_result_for_segments(
+        &ContextView::with_identity(ec, Some(identity)),
+        get_sorted_segment_contexts(ec),
+        options,
+        None,
+    )
+}
+
+/// Evaluates the context for each identity in parallel
+///
+/// The context is compiled once and shared by every identity instead of being copied
+/// per identity, so segment rules are parsed once, segments that only depend on
+/// environment properties are matched once, and flags served with their environment
+/// default whatever the identity are built once. Results are paired with the identifier
+/// they belong to, in no particular order.
+#[cfg(feature = "rayon")]
+pub fn evaluate_identities<I>(
+    ec: &EngineEvaluationContext,
+    identities: I,
+) -> impl ParallelIterator<Item = (String, EvaluationResult)>
+where
+    I: IntoParallelIterator<Item = IdentityContext>,
+{
+    let compiled = CompiledContext::new(ec.clone());
+    identities
+        .into_par_iter()
+        .map(move |identity| compiled.evaluate_identity(identity))
+}
+
+/// Evaluates the context against segments given in evaluation order
+pub(crate) fn get_evaluation_result_for_segments<'a>(
+    ec: &ContextView<'a>,
+    segment_contexts: impl Iterator<Item = (&'a str, &'a SegmentContext, Option<&'a CompiledSegment>)>,
+    options: &EvaluationOptions,
+    trace: Option<&mut EvaluationTrace>,
+) -> EvaluationResult {
+    set_evaluation_time(ec, options);
+
+    // Segments are not evaluated once the organisation stopped serving flags
+    if ec.context.environment.stop_serving_flags {
+        let flags = ec
+            .context
+            .features
+            .values()
+            .filter_map(|fc| {
+                get_stopped_flag_result(ec, fc, options).map(|flag| (fc.name.clone(), flag))
+            })
+            .collect();
+        return EvaluationResult {
+            flags,
+            segments: vec![],
+        };
+    }
+
     // Process segments
-    let (segments, segment_feature_contexts) = get_matching_segments_and_overrides(ec);
+    let (segments, segment_overrides) =
+        get_matching_segments_and_overrides(ec, segment_contexts, options, trace);
 
     // Get flag results
-    let flags = get_flag_results(ec, &segment_feature_contexts);
+    let flags = get_flag_results(ec, &segment_overrides, options);
 
     EvaluationResult { flags, segments }
 }
 
+/// Evaluates a single feature, only checking the segments that override it
+///
+/// Gives the same flag result as `get_evaluation_result`, or `None` if the
+/// context has no feature with the given name or the flag is hidden.
+pub fn get_flag_result(ec: &EngineEvaluationContext, feature_name: &str) -> Option<FlagResult> {
+    get_flag_result_with_options(ec, feature_name, &EvaluationOptions::default())
+}
+
+/// Evaluates a single feature, notifying the observers given in `options`
+pub fn get_flag_result_with_options(
+    ec: &EngineEvaluationContext,
+    feature_name: &str,
+    options: &EvaluationOptions,
+) -> Option<FlagResult> {
+    get_flag_result_for_view(&ContextView::from(ec), feature_name, options)
+}
+
+/// Evaluates a single feature for an identity kept apart from the context
+///
+/// Gives the same flag result as `get_evaluation_result_for_identity`, without copying
+/// the context.
+pub fn get_flag_result_for_identity(
+    ec: &EngineEvaluationContext,
+    identity: &IdentityContext,
+    feature_name: &str,
+) -> Option<FlagResult> {
+    get_flag_result_for_identity_with_options(
+        ec,
+        identity,
+        feature_name,
+        &EvaluationOptions::default(),
+    )
+}
+
+/// Evaluates a single feature for an identity kept apart from the context, notifying
+/// the observers given in `options`
+pub fn get_flag_result_for_identity_with_options(
+    ec: &EngineEvaluationContext,
+    identity: &IdentityContext,
+    feature_name: &str,
+    options: &EvaluationOptions,
+) -> Option<FlagResult> {
+    get_flag_result_for_view(
+        &ContextView::with_identity(ec, Some(identity)),
+        feature_name,
+        options,
+    )
+}
+
+/// Evaluates a single feature of a borrowed context, only checking the segments that override it
+fn get_flag_result_for_view(
+    view: &ContextView,
+    feature_name: &str,
+    options: &EvaluationOptions,
+) -> Option<FlagResult> {
+    let ec = view.context;
+    let feature_context = ec.features.get(feature_name)?;
+    let feature_names = get_feature_and_prerequisites(&ec.features, feature_context);
+
+    // Sort segment keys for deterministic ordering
+    let mut segment_keys: Vec<_> = ec
+        .segments
+        .iter()
+        .filter(|(_, sc)| {
+            feature_names
+                .iter()
+                .any(|name| segment_overrides_feature(sc, name))
+        })
+        .map(|(segment_key, _)| segment_key)
+        .collect();
+    segment_keys.sort();
+
+    let segment_contexts = segment_keys
+        .into_iter()
+        .map(|segment_key| (segment_key.as_str(), &ec.segments[segment_key], None));
+
+    get_flag_result_for_segments(view, feature_context, segment_contexts, options)
+}
+
+/// Evaluates a single feature against segments given in evaluation order
+pub(crate) fn get_flag_result_for_segments<'a>(
+    ec: &ContextView<'a>,
+    feature_context: &FeatureContext,
+    segment_contexts: impl Iterator<Item = (&'a str, &'a SegmentContext, Option<&'a CompiledSegment>)>,
+    options: &EvaluationOptions,
+) -> Option<FlagResult> {
+    if ec.context.environment.stop_serving_flags {
+        return get_stopped_flag_result(ec, feature_context, options);
+    }
+    set_evaluation_time(ec, options);
+
+    let (_, segment_overrides) =
+        get_matching_segments_and_overrides(ec, segment_contexts, options, None);
+
+    let identity_key = get_identity_key(ec);
+    if feature_context.prerequisites.is_empty() {
+        let flag_result = get_flag_result_with_overrides(
+            ec,
+            feature_context,
+            &segment_overrides,
+            identity_key.as_ref(),
+            options,
+        )?;
+        notify_flag_evaluated(ec, options, &flag_result);
+        return Some(flag_result);
+    }
+
+    // Prerequisites are evaluated too, but observers are only notified of the requested flag
+    let features = &ec.context.features;
+    let mut flags = HashMap::new();
+    for name in get_feature_and_prerequisites(features, feature_context) {
+        let Some(fc) = features.get(name) else {
+            continue;
+        };
+        if let Some(flag_result) = get_flag_result_with_overrides(
+            ec,
+            fc,
+            &segment_overrides,
+            identity_key.as_ref(),
+            options,
+        ) {
+            flags.insert(fc.name.clone(), flag_result);
+        }
+    }
+    apply_prerequisites(
+        features,
+        std::iter::once(feature_context.name.as_str()),
+        &mut flags,
+    );
+
+    let flag_result = flags.remove(&feature_context.name)?;
+    if ec.context.environment.hide_disabled_flags && !flag_result.enabled {
+        return None;
+    }
+
+    notify_flag_evaluated(ec, options, &flag_result);
+
+    Some(flag_result)
+}
+
 /// Creates a FlagResult from a FeatureContext
 fn get_flag_result_from_feature_context(
     feature_context: &FeatureContext,
     identity_key: Option<&String>,
-    default_reason: String,
+    default_reason: Reason,
+    options: &EvaluationOptions,
 ) -> FlagResult {
     let mut reason = default_reason;
     let mut value = feature_context.value.clone();
@@ -137,12 +802,7 @@ fn get_flag_result_from_feature_context(
         && !feature_context.key.is_empty()
     {
         // Sort variants by priority (lower priority value = higher priority)
-        let mut sorted_variants = feature_context.variants.clone();
-        sorted_variants.sort_by(|a, b| {
-            let pa = get_priority_or_default(a.priority);
-            let pb = get_priority_or_default(b.priority);
-            pa.partial_cmp(&pb).unwrap()
-        });
+        let sorted_variants = get_sorted_variants(feature_context);
 
         // Calculate hash percentage for the identity and feature combination
         let object_ids = vec![feature_context.key.as_str(), identity_key.unwrap().as_str()];
@@ -150,11 +810,17 @@ fn get_flag_result_from_feature_context(
 
         // Select variant based on weighted distribution
         let mut cumulative_weight = 0.0;
-        for variant in &sorted_variants {
+        for (variant_index, variant) in sorted_variants.iter().enumerate() {
             cumulative_weight += variant.weight;
             if (hash_percentage as f64) <= cumulative_weight {
                 value = variant.value.clone();
-                reason = format!("SPLIT; weight={}", variant.weight);
+                reason = Reason::Split {
+                    weight: variant.weight,
+                    variant_index: Some(variant_index),
+                };
+                for observer in &options.observers {
+                    observer.on_variant_selected(feature_context, variant_index, variant);
+                }
                 break;
             }
         }
@@ -173,11 +839,38 @@ fn get_flag_result_from_feature_context(
 mod tests {
     use super::*;
     use crate::engine_eval::context::EnvironmentContext;
+    use crate::engine_eval::trace::ComparisonStrategy;
+    use crate::types::FlagsmithValue;
 
     #[test]
     fn test_get_priority_or_default() {
         assert_eq!(get_priority_or_default(Some(1.0)), 1.0);
         assert_eq!(get_priority_or_default(None), f64::INFINITY);
+        assert_eq!(get_priority_or_default(Some(f64::NAN)), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_nan_variant_priority_is_weakest() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {"identifier": "user", "key": "test_user"},
+            "features": {
+                "feature": {
+                    "key": "1", "name": "feature", "enabled": true, "value": "control",
+                    "variants": [
+                        {"value": "nan", "weight": 100.0, "priority": 0.0},
+                        {"value": "first", "weight": 100.0, "priority": 1.0}
+                    ]
+                }
+            },
+            "segments": {}
+        }))
+        .unwrap();
+        let mut ec = ec;
+        ec.features.get_mut("feature").unwrap().variants[0].priority = Some(f64::NAN);
+
+        let flag = get_flag_result(&ec, "feature").unwrap();
+        assert_eq!(flag.value.value, "first");
     }
 
     #[test]
@@ -186,6 +879,9 @@ mod tests {
             environment: EnvironmentContext {
                 key: "test".to_string(),
                 name: "test".to_string(),
+                hide_disabled_flags: false,
+                stop_serving_flags: false,
+                feature_analytics: false,
             },
             features: HashMap::new(),
             segments: HashMap::new(),
@@ -196,4 +892,797 @@ mod tests {
         assert_eq!(result.flags.len(), 0);
         assert_eq!(result.segments.len(), 0);
     }
+
+    #[test]
+    fn test_evaluation_counter_only_counts_with_feature_analytics() {
+        use crate::engine_eval::observer::EvaluationCounter;
+
+        let mut ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": true, "value": "a", "metadata": {"feature_id": 1}}
+            }
+        }))
+        .unwrap();
+        let counter = EvaluationCounter::new();
+        let options = EvaluationOptions::new().with_observer(&counter);
+
+        get_evaluation_result_with_options(&ec, &options);
+        get_flag_result_with_options(&ec, "feature", &options);
+        assert!(counter.counts().is_empty());
+
+        ec.environment.feature_analytics = true;
+        get_evaluation_result_with_options(&ec, &options);
+        get_flag_result_with_options(&ec, "feature", &options);
+        assert_eq!(counter.get(1).total, 2);
+    }
+
+    #[test]
+    fn test_get_flag_result_only_evaluates_requested_feature() {
+        use crate::engine_eval::observer::EvaluationObserver;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct SegmentRecorder {
+            segments: Mutex<Vec<String>>,
+        }
+
+        impl EvaluationObserver for SegmentRecorder {
+            fn on_segment_matched(&self, segment: &SegmentResult) {
+                self.segments.lock().unwrap().push(segment.name.clone());
+            }
+        }
+
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "features": {
+                "feature_a": {"key": "1", "name": "feature_a", "enabled": false, "value": "a"},
+                "feature_b": {"key": "2", "name": "feature_b", "enabled": false, "value": "b"}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "segment_a",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "IS_NOT_SET", "property": "foo", "value": ""}]}],
+                    "overrides": [{"key": "1", "name": "feature_a", "enabled": true, "value": "override"}]
+                },
+                "2": {
+                    "key": "2",
+                    "name": "segment_b",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "IS_NOT_SET", "property": "foo", "value": ""}]}],
+                    "overrides": [{"key": "2", "name": "feature_b", "enabled": true, "value": "override"}]
+                }
+            }
+        }))
+        .unwrap();
+
+        let recorder = SegmentRecorder::default();
+        let options = EvaluationOptions::new().with_observer(&recorder);
+        let flag = get_flag_result_with_options(&ec, "feature_a", &options).unwrap();
+        assert!(flag.enabled);
+        assert_eq!(flag.value.value, "override");
+        assert_eq!(
+            flag.reason,
+            Reason::TargetingMatch {
+                segment_name: "segment_a".to_string(),
+                segment_key: Some("1".to_string()),
+            }
+        );
+
+        // The segment overriding feature_b matches too, but is not evaluated
+        assert_eq!(*recorder.segments.lock().unwrap(), vec!["segment_a"]);
+        let result = get_evaluation_result(&ec);
+        assert_eq!(result.segments.len(), 2);
+
+        let flag = get_flag_result(&ec, "feature_b").unwrap();
+        assert!(flag.enabled);
+        assert_eq!(
+            flag.reason.to_string(),
+            "TARGETING_MATCH; segment=segment_b"
+        );
+
+        assert!(get_flag_result(&ec, "missing").is_none());
+    }
+
+    #[test]
+    fn test_get_evaluation_result_with_trace() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {"identifier": "user", "traits": {"age": 30, "version": "1.2.0"}},
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": false, "value": "default"}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "adults",
+                    "rules": [{
+                        "type": "ALL",
+                        "conditions": [
+                            {"operator": "GREATER_THAN_INCLUSIVE", "property": "age", "value": "18"},
+                            {"operator": "LESS_THAN", "property": "version", "value": "2.0.0:semver"}
+                        ]
+                    }],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "adult", "priority": 2.0}]
+                },
+                "2": {
+                    "key": "2",
+                    "name": "everyone",
+                    "rules": [{
+                        "type": "ANY",
+                        "conditions": [
+                            {"operator": "EQUAL", "property": "missing", "value": "x"},
+                            {"operator": "IS_SET", "property": "age", "value": ""}
+                        ]
+                    }],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "everyone", "priority": 1.0}]
+                },
+                "3": {
+                    "key": "3",
+                    "name": "minors",
+                    "rules": [{
+                        "type": "ALL",
+                        "conditions": [
+                            {"operator": "LESS_THAN", "property": "age", "value": "18"},
+                            {"operator": "IS_SET", "property": "age", "value": ""}
+                        ]
+                    }],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "minor", "priority": 0.0}]
+                }
+            }
+        }))
+        .unwrap();
+
+        let (result, trace) = get_evaluation_result_with_trace(&ec);
+
+        assert_eq!(result, get_evaluation_result(&ec));
+        assert_eq!(result.flags["feature"].value.value, "everyone");
+
+        let matched: Vec<_> = trace.segments.iter().map(|s| s.matched).collect();
+        assert_eq!(matched, vec![true, true, false]);
+
+        let adult_conditions = &trace.segments[0].rules[0].conditions;
+        assert_eq!(adult_conditions[0].strategy, ComparisonStrategy::Integer);
+        assert_eq!(adult_conditions[1].strategy, ComparisonStrategy::Semver);
+        assert!(adult_conditions.iter().all(|c| c.matched));
+
+        let everyone_conditions = &trace.segments[1].rules[0].conditions;
+        assert_eq!(everyone_conditions[0].strategy, ComparisonStrategy::Skipped);
+        assert!(everyone_conditions[0].context_value.is_none());
+        assert_eq!(
+            everyone_conditions[1].strategy,
+            ComparisonStrategy::Presence
+        );
+
+        // Short-circuited conditions are not visited
+        let minor_rule = &trace.segments[2].rules[0];
+        assert!(!minor_rule.matched);
+        assert_eq!(minor_rule.conditions.len(), 1);
+
+        let overrides = &trace.flags["feature"].overrides;
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides[0].segment_name, "adults");
+        assert_eq!(overrides[0].priority, Some(2.0));
+        assert!(!overrides[0].selected);
+        assert_eq!(overrides[1].segment_name, "everyone");
+        assert!(overrides[1].selected);
+    }
+
+    #[test]
+    fn test_get_evaluation_result_with_options_notifies_observers() {
+        use crate::engine_eval::context::FeatureValue;
+        use crate::engine_eval::observer::{EvaluationCounter, EvaluationObserver};
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct RecordingObserver {
+            events: Mutex<Vec<String>>,
+        }
+
+        impl EvaluationObserver for RecordingObserver {
+            fn on_segment_matched(&self, segment: &SegmentResult) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("segment:{}", segment.name));
+            }
+
+            fn on_variant_selected(
+                &self,
+                feature_context: &FeatureContext,
+                variant_index: usize,
+                _variant: &FeatureValue,
+            ) {
+                self.events.lock().unwrap().push(format!(
+                    "variant:{}:{}",
+                    feature_context.name, variant_index
+                ));
+            }
+
+            fn on_flag_evaluated(&self, flag: &FlagResult) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("flag:{}", flag.name));
+            }
+        }
+
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test", "feature_analytics": true},
+            "identity": {"identifier": "user", "key": "test_user"},
+            "features": {
+                "feature": {
+                    "key": "1",
+                    "name": "feature",
+                    "enabled": true,
+                    "value": "control",
+                    "metadata": {"feature_id": 7},
+                    "variants": [{"value": "variant", "weight": 100.0}]
+                }
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "everyone",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "IS_NOT_SET", "property": "foo", "value": ""}]}]
+                }
+            }
+        }))
+        .unwrap();
+        let recorder = RecordingObserver::default();
+        let counter = EvaluationCounter::new();
+        let options = EvaluationOptions::new()
+            .with_observer(&recorder)
+            .with_observer(&counter);
+
+        let result = get_evaluation_result_with_options(&ec, &options);
+
+        assert_eq!(result, get_evaluation_result(&ec));
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec!["segment:everyone", "variant:feature:0", "flag:feature"]
+        );
+        assert_eq!(counter.get(7).total, 1);
+        assert_eq!(counter.get(7).by_reason["SPLIT; weight=100"], 1);
+    }
+
+    #[test]
+    fn test_get_evaluation_result_hides_disabled_flags() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test", "hide_disabled_flags": true},
+            "features": {
+                "enabled": {"key": "1", "name": "enabled", "enabled": true, "value": null},
+                "disabled": {"key": "2", "name": "disabled", "enabled": false, "value": null},
+                "overridden_on": {"key": "3", "name": "overridden_on", "enabled": false, "value": null},
+                "overridden_off": {"key": "4", "name": "overridden_off", "enabled": true, "value": null}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "everyone",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "IS_NOT_SET", "property": "foo", "value": ""}]}],
+                    "overrides": [
+                        {"key": "3", "name": "overridden_on", "enabled": true, "value": null},
+                        {"key": "4", "name": "overridden_off", "enabled": false, "value": null}
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+
+        let result = get_evaluation_result(&ec);
+
+        let mut flag_names: Vec<_> = result.flags.keys().cloned().collect();
+        flag_names.sort();
+        assert_eq!(flag_names, vec!["enabled", "overridden_on"]);
+        assert!(get_flag_result(&ec, "overridden_on").is_some());
+        assert!(get_flag_result(&ec, "overridden_off").is_none());
+        assert!(get_flag_result(&ec, "disabled").is_none());
+    }
+
+    #[test]
+    fn test_get_evaluation_result_when_serving_stopped() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test", "stop_serving_flags": true},
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": true, "value": "default"}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "everyone",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "IS_NOT_SET", "property": "foo", "value": ""}]}],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "override"}]
+                }
+            }
+        }))
+        .unwrap();
+
+        let result = get_evaluation_result(&ec);
+        assert!(result.flags.is_empty());
+        assert!(result.segments.is_empty());
+        assert!(get_flag_result(&ec, "feature").is_none());
+
+        let options =
+            EvaluationOptions::new().with_stop_serving_flags(StopServingFlagsBehaviour::Disabled);
+        let result = get_evaluation_result_with_options(&ec, &options);
+        assert!(result.segments.is_empty());
+        let flag = &result.flags["feature"];
+        assert!(!flag.enabled);
+        assert_eq!(flag.value.value, "default");
+        assert_eq!(flag.reason, Reason::ServingStopped);
+        assert_eq!(
+            get_flag_result_with_options(&ec, "feature", &options).as_ref(),
+            Some(flag)
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_evaluate_identities() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": false, "value": "default"}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "admins",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "EQUAL", "property": "$.identity.identifier", "value": "admin"}]}],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "admin"}]
+                }
+            }
+        }))
+        .unwrap();
+        let identities: Vec<IdentityContext> = serde_json::from_value(serde_json::json!([
+            {"identifier": "admin", "key": "test_admin"},
+            {"identifier": "user", "key": "test_user"}
+        ]))
+        .unwrap();
+
+        let results: HashMap<_, _> = evaluate_identities(&ec, identities).collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results["admin"].flags["feature"].enabled);
+        assert_eq!(results["admin"].segments.len(), 1);
+        assert!(!results["user"].flags["feature"].enabled);
+        assert!(results["user"].segments.is_empty());
+    }
+
+    #[test]
+    fn test_get_evaluation_result_for_identity() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {"identifier": "ignored", "key": "test_ignored"},
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": false, "value": "default"}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "admins",
+                    "rules": [{"type": "ALL", "conditions": [
+                        {"operator": "EQUAL", "property": "$.identity.identifier", "value": "admin"},
+                        {"operator": "EQUAL", "property": "role", "value": "owner"}
+                    ]}],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "admin"}]
+                }
+            }
+        }))
+        .unwrap();
+        let identity: IdentityContext = serde_json::from_value(serde_json::json!({
+            "identifier": "admin",
+            "key": "test_admin",
+            "traits": {"role": "owner"}
+        }))
+        .unwrap();
+
+        let result = get_evaluation_result_for_identity(&ec, &identity);
+
+        let mut identity_ec = ec.clone();
+        identity_ec.identity = Some(identity.clone());
+        assert_eq!(result, get_evaluation_result(&identity_ec));
+        assert!(result.flags["feature"].enabled);
+        assert_eq!(
+            get_flag_result_for_identity(&ec, &identity, "feature").as_ref(),
+            Some(&result.flags["feature"])
+        );
+        assert!(!get_evaluation_result(&ec).flags["feature"].enabled);
+    }
+
+    #[test]
+    fn test_get_evaluation_result_with_json_values() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {
+                "identifier": "user",
+                "key": "test_user",
+                "traits": {"config": {"tier": "gold", "regions": ["eu", "us"]}}
+            },
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": true, "value": {"colour": "blue"}}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "gold_in_eu",
+                    "rules": [{"type": "ALL", "conditions": [
+                        {"operator": "EQUAL", "property": "$.identity.traits.config.tier", "value": "gold"},
+                        {"operator": "EQUAL", "property": "$.identity.traits.config.regions[0]", "value": "eu"},
+                        {"operator": "IS_SET", "property": "config", "value": ""}
+                    ]}],
+                    "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": ["a", "b"]}]
+                }
+            }
+        }))
+        .unwrap();
+
+        let result = get_evaluation_result(&ec);
+
+        assert_eq!(result.segments.len(), 1);
+        let flag = &result.flags["feature"];
+        assert_eq!(flag.value.as_json().unwrap(), serde_json::json!(["a", "b"]));
+        assert_eq!(
+            serde_json::to_value(flag).unwrap()["value"],
+            serde_json::json!(["a", "b"])
+        );
+
+        let mut ec = ec;
+        ec.segments.clear();
+        let result = get_evaluation_result(&ec);
+        assert_eq!(
+            result.flags["feature"].value.as_json().unwrap(),
+            serde_json::json!({"colour": "blue"})
+        );
+    }
+
+    fn prerequisites_context() -> EngineEvaluationContext {
+        serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {"identifier": "admin", "key": "test_admin"},
+            "features": {
+                "checkout": {"key": "1", "name": "checkout", "enabled": false, "value": "v1"},
+                "new_cart": {
+                    "key": "2", "name": "new_cart", "enabled": true, "value": "on",
+                    "prerequisites": [{"feature": "checkout"}]
+                },
+                "cart_upsell": {
+                    "key": "3", "name": "cart_upsell", "enabled": true, "value": "on",
+                    "prerequisites": [{"feature": "new_cart"}, {"feature": "checkout", "value": "v2"}]
+                },
+                "loop_a": {
+                    "key": "4", "name": "loop_a", "enabled": true, "value": "",
+                    "prerequisites": [{"feature": "loop_b"}]
+                },
+                "loop_b": {
+                    "key": "5", "name": "loop_b", "enabled": true, "value": "",
+                    "prerequisites": [{"feature": "loop_a"}]
+                },
+                "after_loop": {
+                    "key": "6", "name": "after_loop", "enabled": true, "value": "",
+                    "prerequisites": [{"feature": "loop_a"}]
+                }
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "admins",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "EQUAL", "property": "$.identity.identifier", "value": "admin"}]}],
+                    "overrides": [{"key": "1", "name": "checkout", "enabled": true, "value": "v2"}]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_evaluation_result_resolves_prerequisites() {
+        let mut ec = prerequisites_context();
+
+        let result = get_evaluation_result(&ec);
+        assert!(result.flags["checkout"].enabled);
+        assert!(result.flags["new_cart"].enabled);
+        assert!(result.flags["cart_upsell"].enabled);
+        assert_eq!(result.flags["loop_a"].reason, Reason::PrerequisiteCycle);
+        assert_eq!(result.flags["loop_b"].reason, Reason::PrerequisiteCycle);
+        let after_loop = &result.flags["after_loop"];
+        assert!(!after_loop.enabled);
+        assert_eq!(
+            after_loop.reason,
+            Reason::PrerequisiteNotMet {
+                feature: "loop_a".to_string()
+            }
+        );
+        for (name, flag) in &result.flags {
+            assert_eq!(get_flag_result(&ec, name).as_ref(), Some(flag));
+        }
+
+        // Without the segment override, checkout stays disabled and its dependents follow
+        ec.identity.as_mut().unwrap().identifier = "user".to_string();
+        let result = get_evaluation_result(&ec);
+        for name in ["new_cart", "cart_upsell"] {
+            let flag = &result.flags[name];
+            assert!(!flag.enabled);
+            assert_eq!(flag.value.value, "on");
+            assert_eq!(get_flag_result(&ec, name).as_ref(), Some(flag));
+        }
+        assert_eq!(
+            result.flags["cart_upsell"].reason,
+            Reason::PrerequisiteNotMet {
+                feature: "new_cart".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_evaluation_result_checks_prerequisite_values() {
+        let mut ec = prerequisites_context();
+        ec.features.get_mut("checkout").unwrap().enabled = true;
+        ec.identity.as_mut().unwrap().identifier = "user".to_string();
+
+        let result = get_evaluation_result(&ec);
+
+        assert!(result.flags["new_cart"].enabled);
+        let cart_upsell = &result.flags["cart_upsell"];
+        assert!(!cart_upsell.enabled);
+        assert_eq!(
+            cart_upsell.reason,
+            Reason::PrerequisiteNotMet {
+                feature: "checkout".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_evaluation_result_hides_flags_with_unmet_prerequisites() {
+        let mut ec = prerequisites_context();
+        ec.environment.hide_disabled_flags = true;
+        ec.identity.as_mut().unwrap().identifier = "user".to_string();
+
+        let result = get_evaluation_result(&ec);
+
+        assert!(result.flags.is_empty());
+        assert!(get_flag_result(&ec, "new_cart").is_none());
+    }
+
+    #[test]
+    fn test_get_evaluation_result_with_clock() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {
+                "identifier": "user",
+                "key": "test_user",
+                "traits": {"trial_ends_at": "2024-03-01T00:00:00Z"}
+            },
+            "features": {
+                "upgrade_banner": {"key": "1", "name": "upgrade_banner", "enabled": false, "value": ""}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "trial_expired",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "LESS_THAN", "property": "trial_ends_at", "value": "now:datetime"}]}],
+                    "overrides": [{"key": "1", "name": "upgrade_banner", "enabled": true, "value": ""}]
+                }
+            }
+        }))
+        .unwrap();
+        let before: chrono::DateTime<chrono::Utc> = "2024-02-01T00:00:00Z".parse().unwrap();
+        let after: chrono::DateTime<chrono::Utc> = "2024-04-01T00:00:00Z".parse().unwrap();
+
+        let options = EvaluationOptions::new().with_clock(&before);
+        let result = get_evaluation_result_with_options(&ec, &options);
+        assert!(result.segments.is_empty());
+        assert!(!result.flags["upgrade_banner"].enabled);
+
+        let options = EvaluationOptions::new().with_clock(&after);
+        let result = get_evaluation_result_with_options(&ec, &options);
+        assert_eq!(result.segments[0].name, "trial_expired");
+        assert!(result.flags["upgrade_banner"].enabled);
+        assert_eq!(
+            get_flag_result_with_options(&ec, "upgrade_banner", &options).as_ref(),
+            Some(&result.flags["upgrade_banner"])
+        );
+    }
+
+    fn scheduled_context() -> EngineEvaluationContext {
+        serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {"identifier": "user", "key": "test_user"},
+            "features": {
+                "launch": {
+                    "key": "1",
+                    "name": "launch",
+                    "enabled": true,
+                    "value": "live",
+                    "active_from": "2024-03-01T00:00:00Z"
+                },
+                "sale": {"key": "2", "name": "sale", "enabled": false, "value": "none"}
+            },
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "everyone",
+                    "rules": [{"type": "ALL", "conditions": [{"operator": "IS_SET", "property": "$.identity.identifier", "value": null}]}],
+                    "overrides": [
+                        {
+                            "key": "2",
+                            "name": "sale",
+                            "enabled": true,
+                            "value": "spring",
+                            "priority": 0,
+                            "active_from": "2024-03-01T00:00:00Z",
+                            "active_until": "2024-04-01T00:00:00Z"
+                        },
+                        {"key": "2", "name": "sale", "enabled": true, "value": "always", "priority": 1}
+                    ]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_evaluation_result_applies_schedules() {
+        let ec = scheduled_context();
+        let before: chrono::DateTime<chrono::Utc> = "2024-02-01T00:00:00Z".parse().unwrap();
+        let during: chrono::DateTime<chrono::Utc> = "2024-03-15T00:00:00Z".parse().unwrap();
+        let after: chrono::DateTime<chrono::Utc> = "2024-04-01T00:00:00Z".parse().unwrap();
+
+        let result =
+            get_evaluation_result_with_options(&ec, &EvaluationOptions::new().with_clock(&before));
+        assert!(!result.flags["launch"].enabled);
+        assert_eq!(result.flags["launch"].reason, Reason::Scheduled);
+        assert_eq!(result.flags["sale"].value, FlagsmithValue::string("always"));
+
+        let result =
+            get_evaluation_result_with_options(&ec, &EvaluationOptions::new().with_clock(&during));
+        assert!(result.flags["launch"].enabled);
+        assert_eq!(result.flags["launch"].reason, Reason::Default);
+        assert_eq!(result.flags["sale"].value, FlagsmithValue::string("spring"));
+
+        // `active_until` is exclusive
+        let options = EvaluationOptions::new().with_clock(&after);
+        let result = get_evaluation_result_with_options(&ec, &options);
+        assert!(result.flags["launch"].enabled);
+        assert_eq!(result.flags["sale"].value, FlagsmithValue::string("always"));
+        assert_eq!(
+            get_flag_result_with_options(&ec, "sale", &options).as_ref(),
+            Some(&result.flags["sale"])
+        );
+    }
+
+    #[test]
+    fn test_get_evaluation_result_reports_inactive_overrides_as_scheduled() {
+        let mut ec = scheduled_context();
+        ec.segments.get_mut("1").unwrap().overrides.truncate(1);
+        let before: chrono::DateTime<chrono::Utc> = "2024-02-01T00:00:00Z".parse().unwrap();
+        let during: chrono::DateTime<chrono::Utc> = "2024-03-15T00:00:00Z".parse().unwrap();
+
+        // The environment default is served because the override is outside its schedule
+        let options = EvaluationOptions::new().with_clock(&before);
+        let result = get_evaluation_result_with_options(&ec, &options);
+        assert!(!result.flags["sale"].enabled);
+        assert_eq!(result.flags["sale"].value, FlagsmithValue::string("none"));
+        assert_eq!(result.flags["sale"].reason, Reason::Scheduled);
+        assert_eq!(
+            get_flag_result_with_options(&ec, "sale", &options).as_ref(),
+            Some(&result.flags["sale"])
+        );
+
+        let options = EvaluationOptions::new().with_clock(&during);
+        let result = get_evaluation_result_with_options(&ec, &options);
+        assert_eq!(result.flags["sale"].value, FlagsmithValue::string("spring"));
+        assert_eq!(
+            result.flags["sale"].reason,
+            Reason::TargetingMatch {
+                segment_name: "everyone".to_string(),
+                segment_key: Some("1".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_evaluation_result_applies_schedules_without_timezone() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "features": {
+                "launch": {
+                    "key": "1",
+                    "name": "launch",
+                    "enabled": true,
+                    "value": "live",
+                    "active_from": "2024-03-01T00:00:00"
+                }
+            }
+        }))
+        .unwrap();
+        let active_from: chrono::DateTime<chrono::Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        let before = active_from - chrono::TimeDelta::seconds(1);
+
+        // Times without a timezone are in UTC, like `:datetime` condition values
+        assert_eq!(ec.features["launch"].active_from, Some(active_from));
+        let options = EvaluationOptions::new().with_clock(&before);
+        let flag = get_flag_result_with_options(&ec, "launch", &options).unwrap();
+        assert_eq!(flag.reason, Reason::Scheduled);
+        let options = EvaluationOptions::new().with_clock(&active_from);
+        assert!(
+            get_flag_result_with_options(&ec, "launch", &options)
+                .unwrap()
+                .enabled
+        );
+    }
+
+    #[test]
+    fn test_get_evaluation_result_hides_scheduled_flags() {
+        let mut ec = scheduled_context();
+        ec.environment.hide_disabled_flags = true;
+        let before: chrono::DateTime<chrono::Utc> = "2024-02-01T00:00:00Z".parse().unwrap();
+        let options = EvaluationOptions::new().with_clock(&before);
+
+        let result = get_evaluation_result_with_options(&ec, &options);
+
+        assert!(!result.flags.contains_key("launch"));
+        assert_eq!(get_flag_result_with_options(&ec, "launch", &options), None);
+    }
+
+    #[test]
+    fn test_get_evaluation_result_with_trace_records_inactive_overrides() {
+        let ec = scheduled_context();
+        let after: chrono::DateTime<chrono::Utc> = "2024-04-01T00:00:00Z".parse().unwrap();
+
+        let options = EvaluationOptions::new().with_clock(&after);
+        let (result, trace) = get_evaluation_result_with_trace_with_options(&ec, &options);
+
+        assert_eq!(result.flags["sale"].value, FlagsmithValue::string("always"));
+        let overrides = &trace.flags["sale"].overrides;
+        assert_eq!(overrides.len(), 2);
+        assert!(!overrides[0].active);
+        assert!(!overrides[0].selected);
+        assert!(overrides[1].active);
+        assert!(overrides[1].selected);
+    }
+
+    #[test]
+    fn test_get_flag_result_with_segment_cycle_matches_full_evaluation() {
+        let ec: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "features": {
+                "fa": {"key": "1", "name": "fa", "enabled": false, "value": "a"},
+                "fb": {"key": "2", "name": "fb", "enabled": false, "value": "b"}
+            },
+            "segments": {
+                "A": {
+                    "key": "A",
+                    "name": "A",
+                    "rules": [{"type": "NONE", "conditions": [{"operator": "IN_SEGMENT", "property": "", "value": "B"}]}],
+                    "overrides": [{"key": "1", "name": "fa", "enabled": true, "value": "a"}]
+                },
+                "B": {
+                    "key": "B",
+                    "name": "B",
+                    "rules": [{"type": "NONE", "conditions": [{"operator": "IN_SEGMENT", "property": "", "value": "A"}]}],
+                    "overrides": [{"key": "2", "name": "fb", "enabled": true, "value": "b"}]
+                }
+            }
+        }))
+        .unwrap();
+        let compiled = crate::engine_eval::compiled::CompiledContext::new(ec.clone());
+
+        let result = get_evaluation_result(&ec);
+        let (traced_result, _) = get_evaluation_result_with_trace(&ec);
+
+        // Segments in a cycle never match, whichever is evaluated first
+        assert!(result.segments.is_empty());
+        assert_eq!(traced_result, result);
+        assert_eq!(compiled.get_evaluation_result(), result);
+        for feature_name in ["fa", "fb"] {
+            let flag = &result.flags[feature_name];
+            assert!(!flag.enabled);
+            assert_eq!(flag.reason, Reason::Default);
+            assert_eq!(get_flag_result(&ec, feature_name).as_ref(), Some(flag));
+            assert_eq!(compiled.get_flag_result(feature_name).as_ref(), Some(flag));
+        }
+    }
 }
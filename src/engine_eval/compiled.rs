@@ -0,0 +1,829 @@
+use super::context::{
+    Condition, ConditionOperator, ContextView, EngineEvaluationContext, FeatureContext,
+    IdentityContext, SegmentContext, SegmentRule,
+};
+use super::options::EvaluationOptions;
+use super::result::{EvaluationResult, FlagResult};
+use super::segment_evaluator::{
+    build_regex, get_cyclic_segment_keys, is_context_in_compiled_segment,
+    is_identity_independent_rule, parse_cidr_condition_value, parse_datetime_condition_value,
+    parse_semver_condition_value, parse_semver_list_condition_value,
+    parse_semver_range_condition_value, parse_version_condition_value, CidrBlock, DatetimeOperand,
+    LooseVersion,
+};
+use super::trace::EvaluationTrace;
+use crate::engine;
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use regex::Regex;
+use semver::{Version, VersionReq};
+use serde_json_path::JsonPath;
+use std::collections::{HashMap, HashSet};
+
+/// An evaluation context prepared once for repeated evaluation.
+///
+/// Compiling a context parses the regexes, `:semver` versions and ranges, `:version` versions,
+/// `:datetime` datetimes,
+/// JSONPath properties, `IN` value lists and `IN_CIDR` blocks of every segment condition, and sorts feature
+/// variants by priority, so that evaluating it does not repeat that work. Segments whose rules
+/// only depend on environment properties are matched once, for every identity, and so are
+/// the flags of features without variants or schedule that no segment overrides.
+#[derive(Clone, Debug)]
+pub struct CompiledContext {
+    context: EngineEvaluationContext,
+    segments: Vec<CompiledSegment>,
+    static_flag_results: HashMap<String, FlagResult>,
+}
+
+/// Pre-parsed rules of a segment, stored in evaluation order.
+#[derive(Clone, Debug)]
+pub(crate) struct CompiledSegment {
+    /// The key of the segment in `EngineEvaluationContext.segments`.
+    pub(crate) key: String,
+    /// Compiled rules, in the same order as `SegmentContext.rules`.
+    pub(crate) rules: Vec<CompiledSegmentRule>,
+    /// Whether the context is in the segment, for segments that match the same way
+    /// for every identity.
+    pub(crate) static_match: Option<bool>,
+    /// Whether the segment references itself through IN_SEGMENT conditions, so that
+    /// the context is never in it.
+    pub(crate) in_cycle: bool,
+}
+
+/// Pre-parsed counterpart of a `SegmentRule`.
+#[derive(Clone, Debug)]
+pub(crate) struct CompiledSegmentRule {
+    /// Compiled conditions, in the same order as `SegmentRule.conditions`.
+    pub(crate) conditions: Vec<CompiledCondition>,
+    /// Compiled nested rules, in the same order as `SegmentRule.rules`.
+    pub(crate) rules: Vec<CompiledSegmentRule>,
+}
+
+/// Pre-parsed operands of a `Condition`.
+#[derive(Clone, Debug)]
+pub(crate) struct CompiledCondition {
+    /// The condition value as a single string.
+    pub(crate) value: String,
+    /// The parsed property, for properties starting with `$.`.
+    pub(crate) json_path: Option<JsonPath>,
+    /// The compiled pattern, for valid `REGEX` conditions.
+    pub(crate) regex: Option<Regex>,
+    /// The parsed version, for valid `:semver` suffixed condition values.
+    pub(crate) semver: Option<Version>,
+    /// The parsed version, for valid `:version` suffixed condition values.
+    pub(crate) version: Option<LooseVersion>,
+    /// The parsed version requirement, for valid `SEMVER_RANGE` conditions.
+    pub(crate) semver_range: Option<VersionReq>,
+    /// The parsed datetime, for valid `:datetime` suffixed condition values.
+    pub(crate) datetime: Option<DatetimeOperand>,
+    /// The accepted values, for `IN` and `CONTAINS_ANY` conditions, or the required values,
+    /// for `CONTAINS_ALL` conditions.
+    pub(crate) in_values: Option<HashSet<String>>,
+    /// The accepted versions, for `IN` conditions with valid `:semver` suffixed value lists.
+    pub(crate) in_versions: Option<Vec<Version>>,
+    /// The accepted networks, for valid `IN_CIDR` conditions.
+    pub(crate) cidr_blocks: Option<Vec<CidrBlock>>,
+}
+
+impl CompiledContext {
+    /// Compiles an evaluation context
+    pub fn new(mut context: EngineEvaluationContext) -> Self {
+        for feature_context in context.features.values_mut() {
+            sort_variants(feature_context);
+        }
+
+        for segment_context in context.segments.values_mut() {
+            for override_fc in segment_context.overrides.iter_mut() {
+                sort_variants(override_fc);
+            }
+        }
+
+        // Segments that do not depend on the identity are matched without one
+        let anonymous_view = ContextView::with_identity(&context, None);
+        let cyclic_segment_keys = get_cyclic_segment_keys(&context);
+        let mut segments: Vec<_> = context
+            .segments
+            .iter()
+            .map(|(key, segment_context)| {
+                let in_cycle = cyclic_segment_keys.contains(key.as_str());
+                compile_segment(&anonymous_view, key, segment_context, in_cycle)
+            })
+            .collect();
+
+        // Sort segment keys for deterministic ordering
+        segments.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let static_flag_results = get_static_flag_results(&context);
+
+        CompiledContext {
+            context,
+            segments,
+            static_flag_results,
+        }
+    }
+
+    /// The evaluation context this was compiled from, with variants sorted by priority
+    pub fn context(&self) -> &EngineEvaluationContext {
+        &self.context
+    }
+
+    /// Borrows the compiled context for evaluation
+    fn view(&self) -> ContextView<'_> {
+        self.view_for(self.context.identity.as_ref())
+    }
+
+    /// Borrows the compiled context for evaluation for the given identity
+    fn view_for<'a>(&'a self, identity: Option<&'a IdentityContext>) -> ContextView<'a> {
+        ContextView::with_identity(&self.context, identity)
+            .with_compiled_segments(&self.segments)
+            .with_static_flag_results(&self.static_flag_results)
+    }
+
+    /// Evaluates the compiled context, with the same semantics as `engine::get_evaluation_result`
+    pub fn get_evaluation_result(&self) -> EvaluationResult {
+        self.get_evaluation_result_with_options(&EvaluationOptions::default())
+    }
+
+    /// Evaluates the compiled context, with the same semantics as
+    /// `engine::get_evaluation_result_with_options`
+    pub fn get_evaluation_result_with_options(
+        &self,
+        options: &EvaluationOptions,
+    ) -> EvaluationResult {
+        engine::get_evaluation_result_for_segments(
+            &self.view(),
+            self.segment_contexts(),
+            options,
+            None,
+        )
+    }
+
+    /// Evaluates the compiled context for an identity, with the same semantics as
+    /// `engine::get_evaluation_result_for_identity`
+    pub fn get_evaluation_result_for_identity(
+        &self,
+        identity: &IdentityContext,
+    ) -> EvaluationResult {
+        self.get_evaluation_result_for_identity_with_options(
+            identity,
+            &EvaluationOptions::default(),
+        )
+    }
+
+    /// Evaluates the compiled context for an identity, with the same semantics as
+    /// `engine::get_evaluation_result_for_identity_with_options`
+    pub fn get_evaluation_result_for_identity_with_options(
+        &self,
+        identity: &IdentityContext,
+        options: &EvaluationOptions,
+    ) -> EvaluationResult {
+        engine::get_evaluation_result_for_segments(
+            &self.view_for(Some(identity)),
+            self.segment_contexts(),
+            options,
+            None,
+        )
+    }
+
+    /// Evaluates the compiled context and explains how the result was reached,
+    /// with the same semantics as `engine::get_evaluation_result_with_trace`
+    pub fn get_evaluation_result_with_trace(&self) -> (EvaluationResult, EvaluationTrace) {
+        self.get_evaluation_result_with_trace_with_options(&EvaluationOptions::default())
+    }
+
+    /// Evaluates the compiled context and explains how the result was reached, with the
+    /// same semantics as `engine::get_evaluation_result_with_trace_with_options`
+    pub fn get_evaluation_result_with_trace_with_options(
+        &self,
+        options: &EvaluationOptions,
+    ) -> (EvaluationResult, EvaluationTrace) {
+        let mut trace = EvaluationTrace::default();
+        let result = engine::get_evaluation_result_for_segments(
+            &self.view(),
+            self.segment_contexts(),
+            options,
+            Some(&mut trace),
+        );
+        (result, trace)
+    }
+
+    /// Evaluates a single feature, with the same semantics as `engine::get_flag_result`
+    pub fn get_flag_result(&self, feature_name: &str) -> Option<FlagResult> {
+        self.get_flag_result_with_options(feature_name, &EvaluationOptions::default())
+    }
+
+    /// Evaluates a single feature, with the same semantics as
+    /// `engine::get_flag_result_with_options`
+    pub fn get_flag_result_with_options(
+        &self,
+        feature_name: &str,
+        options: &EvaluationOptions,
+    ) -> Option<FlagResult> {
+        self.get_flag_result_for_view(&self.view(), feature_name, options)
+    }
+
+    /// Evaluates a single feature for an identity, with the same semantics as
+    /// `engine::get_flag_result_for_identity`
+    pub fn get_flag_result_for_identity(
+        &self,
+        identity: &IdentityContext,
+        feature_name: &str,
+    ) -> Option<FlagResult> {
+        self.get_flag_result_for_identity_with_options(
+            identity,
+            feature_name,
+            &EvaluationOptions::default(),
+        )
+    }
+
+    /// Evaluates a single feature for an identity, with the same semantics as
+    /// `engine::get_flag_result_for_identity_with_options`
+    pub fn get_flag_result_for_identity_with_options(
+        &self,
+        identity: &IdentityContext,
+        feature_name: &str,
+        options: &EvaluationOptions,
+    ) -> Option<FlagResult> {
+        self.get_flag_result_for_view(&self.view_for(Some(identity)), feature_name, options)
+    }
+
+    /// Evaluates the compiled context for each identity in parallel
+    ///
+    /// Identities are evaluated against the shared context without copying it, replacing
+    /// the identity of the context. Results are paired with the identifier they belong to,
+    /// in no particular order.
+    #[cfg(feature = "rayon")]
+    pub fn evaluate_identities<'a, I>(
+        &'a self,
+        identities: I,
+    ) -> impl ParallelIterator<Item = (String, EvaluationResult)> + 'a
+    where
+        I: IntoParallelIterator<Item = IdentityContext>,
+        I::Iter: 'a,
+    {
+        identities
+            .into_par_iter()
+            .map(move |identity| self.evaluate_identity(identity))
+    }
+
+    /// Evaluates the compiled context for an identity, keyed by its identifier
+    #[cfg(feature = "rayon")]
+    pub(crate) fn evaluate_identity(
+        &self,
+        identity: IdentityContext,
+    ) -> (String, EvaluationResult) {
+        let result = self.get_evaluation_result_for_identity(&identity);
+        (identity.identifier, result)
+    }
+
+    /// Evaluates a single feature of the borrowed compiled context
+    fn get_flag_result_for_view<'a>(
+        &'a self,
+        view: &ContextView<'a>,
+        feature_name: &str,
+        options: &EvaluationOptions,
+    ) -> Option<FlagResult> {
+        let feature_context = self.context.features.get(feature_name)?;
+
+        let feature_names =
+            engine::get_feature_and_prerequisites(&self.context.features, feature_context);
+        let segment_contexts = self.segment_contexts().filter(move |(_, sc, _)| {
+            feature_names
+                .iter()
+                .any(|name| engine::segment_overrides_feature(sc, name))
+        });
+
+        engine::get_flag_result_for_segments(view, feature_context, segment_contexts, options)
+    }
+
+    /// Gets the segments in evaluation order, paired with their compiled counterparts
+    fn segment_contexts(
+        &self,
+    ) -> impl Iterator<Item = (&str, &SegmentContext, Option<&CompiledSegment>)> {
+        self.segments.iter().map(|segment| {
+            (
+                segment.key.as_str(),
+                &self.context.segments[&segment.key],
+                Some(segment),
+            )
+        })
+    }
+}
+
+impl From<EngineEvaluationContext> for CompiledContext {
+    fn from(context: EngineEvaluationContext) -> Self {
+        CompiledContext::new(context)
+    }
+}
+
+/// Gets the flag results that are the same for every identity, by feature name
+///
+/// These are the flags of the features without variants or schedule that no segment
+/// overrides, leaving out disabled flags the environment hides. Prerequisites are not checked.
+fn get_static_flag_results(context: &EngineEvaluationContext) -> HashMap<String, FlagResult> {
+    let overridden_features: HashSet<&str> = context
+        .segments
+        .values()
+        .flat_map(|segment_context| &segment_context.overrides)
+        .map(|override_fc| override_fc.name.as_str())
+        .collect();
+
+    context
+        .features
+        .values()
+        .filter(|fc| {
+            fc.variants.is_empty()
+                && !fc.is_scheduled()
+                && !overridden_features.contains(fc.name.as_str())
+        })
+        .filter_map(|fc| engine::get_default_flag_result(context, fc))
+        .map(|flag_result| (flag_result.name.clone(), flag_result))
+        .collect()
+}
+
+/// Sorts the variants of a feature context by priority
+fn sort_variants(feature_context: &mut FeatureContext) {
+    feature_context
+        .variants
+        .sort_by(engine::compare_variant_priority);
+}
+
+/// Compiles the rules of a segment, matching it up front if no rule depends on the identity
+///
+/// `anonymous_view` is expected to have no identity.
+fn compile_segment(
+    anonymous_view: &ContextView,
+    key: &str,
+    segment_context: &SegmentContext,
+    in_cycle: bool,
+) -> CompiledSegment {
+    let rules: Vec<_> = segment_context.rules.iter().map(compile_rule).collect();
+
+    let identity_independent =
+        segment_context
+            .rules
+            .iter()
+            .zip(&rules)
+            .all(|(rule, compiled_rule)| {
+                is_identity_independent_rule(anonymous_view, rule, compiled_rule)
+            });
+    let static_match = identity_independent.then(|| {
+        is_context_in_compiled_segment(anonymous_view, segment_context, Some(&rules), None)
+    });
+
+    CompiledSegment {
+        key: key.to_string(),
+        rules,
+        static_match,
+        in_cycle,
+    }
+}
+
+/// Compiles a segment rule and its nested rules
+fn compile_rule(rule: &SegmentRule) -> CompiledSegmentRule {
+    CompiledSegmentRule {
+        conditions: rule.conditions.iter().map(compile_condition).collect(),
+        rules: rule.rules.iter().map(compile_rule).collect(),
+    }
+}
+
+/// Parses the operands the condition operator needs
+fn compile_condition(condition: &Condition) -> CompiledCondition {
+    let value = condition.value.as_string();
+
+    let json_path = if condition.property.starts_with("$.") {
+        JsonPath::parse(&condition.property).ok()
+    } else {
+        None
+    };
+
+    let regex = match condition.operator {
+        ConditionOperator::Regex => build_regex(&value).ok(),
+        _ => None,
+    };
+
+    let in_values = match condition.operator {
+        ConditionOperator::In
+        | ConditionOperator::NotIn
+        | ConditionOperator::ContainsAny
+        | ConditionOperator::ContainsAll => Some(condition.value.as_vec().into_iter().collect()),
+        ConditionOperator::InIgnoreCase => Some(
+            condition
+                .value
+                .as_vec()
+                .iter()
+                .map(|value| value.to_lowercase())
+                .collect(),
+        ),
+        _ => None,
+    };
+
+    let in_versions = match condition.operator {
+        ConditionOperator::In | ConditionOperator::NotIn | ConditionOperator::InIgnoreCase => {
+            parse_semver_list_condition_value(&condition.value)
+        }
+        _ => None,
+    };
+
+    let cidr_blocks = match condition.operator {
+        ConditionOperator::InCidr => parse_cidr_condition_value(&condition.value),
+        _ => None,
+    };
+
+    let semver_range = match condition.operator {
+        ConditionOperator::SemverRange => parse_semver_range_condition_value(&value),
+        _ => None,
+    };
+
+    CompiledCondition {
+        semver: parse_semver_condition_value(&value),
+        semver_range,
+        version: parse_version_condition_value(&value),
+        datetime: parse_datetime_condition_value(&value),
+        value,
+        json_path,
+        regex,
+        in_values,
+        in_versions,
+        cidr_blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> EngineEvaluationContext {
+        serde_json::from_value(serde_json::json!({
+            "environment": {"key": "key", "name": "Environment"},
+            "identity": {
+                "identifier": "user",
+                "key": "key_user",
+                "traits": {"email": "user@example.com", "version": "2.1.0", "plan": "pro"}
+            },
+            "features": {
+                "feature": {
+                    "key": "1",
+                    "name": "feature",
+                    "enabled": false,
+                    "value": "control",
+                    "variants": [
+                        {"value": "low", "weight": 100.0, "priority": 2.0},
+                        {"value": "high", "weight": 100.0, "priority": 1.0}
+                    ]
+                }
+            },
+            "segments": {
+                "2": {
+                    "key": "2",
+                    "name": "segment_b",
+                    "rules": [{
+                        "type": "ALL",
+                        "conditions": [
+                            {"operator": "REGEX", "property": "email", "value": ".*@example\\.com"},
+                            {"operator": "GREATER_THAN", "property": "version", "value": "2.0.0:semver"},
+                            {"operator": "IN", "property": "plan", "value": "free, pro"},
+                            {"operator": "IN", "property": "$.identity.identifier", "value": ["user"]}
+                        ]
+                    }],
+                    "overrides": [
+                        {"key": "1", "name": "feature", "enabled": true, "value": "segment", "priority": 1.0}
+                    ]
+                },
+                "1": {
+                    "key": "1",
+                    "name": "segment_a",
+                    "rules": [{
+                        "type": "ANY",
+                        "conditions": [{"operator": "REGEX", "property": "email", "value": "["}]
+                    }]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn compiled_context_sorts_segments_and_variants() {
+        let compiled = CompiledContext::new(context());
+
+        let keys: Vec<_> = compiled.segments.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["1", "2"]);
+
+        let variants = &compiled.context().features["feature"].variants;
+        assert_eq!(variants[0].value.value, "high");
+        assert_eq!(variants[1].value.value, "low");
+    }
+
+    #[test]
+    fn compiled_context_pre_parses_condition_operands() {
+        let compiled = CompiledContext::new(context());
+
+        let conditions = &compiled.segments[1].rules[0].conditions;
+        assert!(conditions[0].regex.is_some());
+        assert_eq!(conditions[1].semver, Some(Version::new(2, 0, 0)));
+        assert_eq!(
+            conditions[2].in_values,
+            Some(HashSet::from(["free".to_string(), "pro".to_string()]))
+        );
+        assert!(conditions[3].json_path.is_some());
+
+        // Invalid patterns are not compiled
+        assert!(compiled.segments[0].rules[0].conditions[0].regex.is_none());
+    }
+
+    #[test]
+    fn compiled_context_evaluates_like_engine() {
+        let ec = context();
+        let expected = engine::get_evaluation_result(&ec);
+
+        let compiled = CompiledContext::new(ec);
+        let result = compiled.get_evaluation_result();
+
+        assert_eq!(result, expected);
+        assert_eq!(
+            compiled.get_flag_result("feature").as_ref(),
+            Some(&expected.flags["feature"])
+        );
+        assert_eq!(result.segments.len(), 1);
+        assert_eq!(result.segments[0].name, "segment_b");
+        assert_eq!(
+            result.flags["feature"].reason.to_string(),
+            "TARGETING_MATCH; segment=segment_b"
+        );
+    }
+
+    #[test]
+    fn compiled_context_matches_environment_only_segments_once() {
+        let mut ec = context();
+        ec.segments.insert(
+            "3".to_string(),
+            serde_json::from_value(serde_json::json!({
+                "key": "3",
+                "name": "segment_c",
+                "rules": [{
+                    "type": "ALL",
+                    "conditions": [{"operator": "EQUAL", "property": "$.environment.name", "value": "Environment"}]
+                }]
+            }))
+            .unwrap(),
+        );
+        let expected = engine::get_evaluation_result(&ec);
+
+        let compiled = CompiledContext::new(ec);
+
+        let static_matches: Vec<_> = compiled.segments.iter().map(|s| s.static_match).collect();
+        assert_eq!(static_matches, vec![None, None, Some(true)]);
+        assert_eq!(compiled.get_evaluation_result(), expected);
+    }
+
+    #[test]
+    fn compiled_context_builds_identity_independent_flags_once() {
+        let mut ec = context();
+        for (name, active_from) in [("plain", None), ("scheduled", Some("2024-01-01T00:00:00Z"))] {
+            ec.features.insert(
+                name.to_string(),
+                serde_json::from_value(serde_json::json!({
+                    "key": name,
+                    "name": name,
+                    "enabled": true,
+                    "value": name,
+                    "active_from": active_from
+                }))
+                .unwrap(),
+            );
+        }
+        let expected = engine::get_evaluation_result(&ec);
+
+        let compiled = CompiledContext::new(ec);
+
+        // Features with variants, a schedule or segment overrides depend on the identity or time
+        let names: Vec<_> = compiled.static_flag_results.keys().collect();
+        assert_eq!(names, vec!["plain"]);
+        assert_eq!(
+            compiled.static_flag_results["plain"],
+            expected.flags["plain"]
+        );
+        assert_eq!(compiled.get_evaluation_result(), expected);
+    }
+
+    #[test]
+    fn compiled_context_does_not_match_relative_datetimes_once() {
+        let mut ec = context();
+        ec.segments.insert(
+            "3".to_string(),
+            serde_json::from_value(serde_json::json!({
+                "key": "3",
+                "name": "segment_c",
+                "rules": [{
+                    "type": "ALL",
+                    "conditions": [
+                        {"operator": "LESS_THAN", "property": "$.environment.name", "value": "now:datetime"},
+                        {"operator": "LESS_THAN", "property": "$.environment.name", "value": "2024-01-01T00:00:00Z:datetime"}
+                    ]
+                }]
+            }))
+            .unwrap(),
+        );
+
+        let compiled = CompiledContext::new(ec);
+
+        let conditions = &compiled.segments[2].rules[0].conditions;
+        assert!(matches!(
+            conditions[0].datetime,
+            Some(DatetimeOperand::Relative(_))
+        ));
+        assert!(matches!(
+            conditions[1].datetime,
+            Some(DatetimeOperand::Absolute(_))
+        ));
+        assert_eq!(compiled.segments[2].static_match, None);
+    }
+
+    #[test]
+    fn compiled_context_lowercases_case_insensitive_in_values() {
+        let condition: Condition = serde_json::from_value(serde_json::json!({
+            "operator": "IN_IGNORE_CASE",
+            "property": "email",
+            "value": ["Admin@Example.com", "user@example.com"]
+        }))
+        .unwrap();
+
+        let compiled = compile_condition(&condition);
+
+        assert_eq!(
+            compiled.in_values,
+            Some(HashSet::from([
+                "admin@example.com".to_string(),
+                "user@example.com".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn compiled_context_pre_parses_semver_ranges_and_lists() {
+        let range: Condition = serde_json::from_value(serde_json::json!({
+            "operator": "SEMVER_RANGE",
+            "property": "version",
+            "value": ">=1.2, <2"
+        }))
+        .unwrap();
+        let list: Condition = serde_json::from_value(serde_json::json!({
+            "operator": "IN",
+            "property": "version",
+            "value": ["1.2.0:semver", "1.3.0+build:semver"]
+        }))
+        .unwrap();
+
+        let compiled_range = compile_condition(&range);
+        let compiled_list = compile_condition(&list);
+
+        assert_eq!(
+            compiled_range.semver_range,
+            Some(VersionReq::parse(">=1.2, <2").unwrap())
+        );
+        assert_eq!(compiled_range.in_versions, None);
+        assert_eq!(
+            compiled_list.in_versions,
+            Some(vec![
+                Version::new(1, 2, 0),
+                Version::parse("1.3.0+build").unwrap()
+            ])
+        );
+        assert_eq!(compiled_list.semver_range, None);
+    }
+
+    #[test]
+    fn compiled_context_pre_parses_loose_versions() {
+        let condition: Condition = serde_json::from_value(serde_json::json!({
+            "operator": "GREATER_THAN",
+            "property": "app_version",
+            "value": "4.12:version"
+        }))
+        .unwrap();
+
+        let compiled = compile_condition(&condition);
+
+        assert_eq!(compiled.version, LooseVersion::parse("4.12.0"));
+        assert_eq!(compiled.semver, None);
+    }
+
+    #[test]
+    fn compiled_context_pre_parses_cidr_blocks() {
+        let mut ec = context();
+        ec.segments.insert(
+            "3".to_string(),
+            serde_json::from_value(serde_json::json!({
+                "key": "3",
+                "name": "office",
+                "rules": [{
+                    "type": "ALL",
+                    "conditions": [{
+                        "operator": "IN_CIDR",
+                        "property": "ip_address",
+                        "value": ["10.0.0.0/8", "2001:db8::/32"]
+                    }]
+                }],
+                "overrides": [{"key": "1", "name": "feature", "enabled": true, "value": "office"}]
+            }))
+            .unwrap(),
+        );
+
+        let compiled = CompiledContext::new(ec);
+
+        assert_eq!(
+            compiled.segments[2].rules[0].conditions[0].cidr_blocks,
+            Some(vec![
+                CidrBlock::parse("10.0.0.0/8").unwrap(),
+                CidrBlock::parse("2001:db8::/32").unwrap()
+            ])
+        );
+        let identity: IdentityContext = serde_json::from_value(serde_json::json!({
+            "identifier": "user",
+            "traits": {"ip_address": "10.1.2.3"}
+        }))
+        .unwrap();
+        let result = compiled.get_evaluation_result_for_identity(&identity);
+        assert!(result.segments.iter().any(|s| s.name == "office"));
+    }
+
+    #[test]
+    fn compiled_context_evaluates_segment_references() {
+        let mut ec = context();
+        ec.segments.insert(
+            "3".to_string(),
+            serde_json::from_value(serde_json::json!({
+                "key": "3",
+                "name": "segment_c",
+                "rules": [{
+                    "type": "ALL",
+                    "conditions": [
+                        {"operator": "IN_SEGMENT", "property": "$.environment.name", "value": "2"}
+                    ]
+                }]
+            }))
+            .unwrap(),
+        );
+        let expected = engine::get_evaluation_result(&ec);
+
+        let compiled = CompiledContext::new(ec);
+
+        // Referenced segments may depend on the identity, so references are never static
+        assert_eq!(compiled.segments[2].static_match, None);
+        let result = compiled.get_evaluation_result();
+        assert_eq!(result, expected);
+        let segment_names: Vec<_> = result.segments.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(segment_names, vec!["segment_b", "segment_c"]);
+    }
+
+    #[test]
+    fn compiled_context_evaluates_prerequisites() {
+        let mut ec = context();
+        ec.features.insert(
+            "dependent".to_string(),
+            serde_json::from_value(serde_json::json!({
+                "key": "2",
+                "name": "dependent",
+                "enabled": true,
+                "value": "on",
+                "prerequisites": [{"feature": "feature", "value": "segment"}]
+            }))
+            .unwrap(),
+        );
+        let expected = engine::get_evaluation_result(&ec);
+
+        let compiled = CompiledContext::new(ec);
+
+        assert!(expected.flags["dependent"].enabled);
+        assert_eq!(compiled.get_evaluation_result(), expected);
+        assert_eq!(
+            compiled.get_flag_result("dependent").as_ref(),
+            Some(&expected.flags["dependent"])
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn compiled_context_evaluates_identities() {
+        let mut ec = context();
+        let identity = ec.identity.take().unwrap();
+        let mut other_identity = identity.clone();
+        other_identity.identifier = "other".to_string();
+
+        let compiled = CompiledContext::new(ec.clone());
+        let mut results: Vec<_> = compiled
+            .evaluate_identities(vec![identity.clone(), other_identity.clone()])
+            .collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(results.len(), 2);
+        for (identifier, identity) in [("other", other_identity), ("user", identity)] {
+            let (result_identifier, result) = results.remove(0);
+            assert_eq!(result_identifier, identifier);
+
+            let mut identity_ec = ec.clone();
+            identity_ec.identity = Some(identity);
+            assert_eq!(result, engine::get_evaluation_result(&identity_ec));
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use super::compiled::CompiledSegment;
+use super::result::FlagResult;
+use super::segment_evaluator::get_cyclic_segment_keys;
 use crate::types::FlagsmithValue;
+use crate::utils::datetime;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -58,6 +64,51 @@ pub struct FeatureContext {
     /// Metadata about the feature.
     #[serde(default)]
     pub metadata: FeatureMetadata,
+    /// Features that must be met for this feature to be enabled. Ignored on segment overrides.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prerequisites: Vec<FeaturePrerequisite>,
+    /// The time this feature context becomes active. It is always active if not set.
+    /// Times without a timezone are in UTC.
+    #[serde(
+        default,
+        with = "datetime::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_from: Option<DateTime<Utc>>,
+    /// The time this feature context stops being active. It never expires if not set.
+    /// Times without a timezone are in UTC.
+    #[serde(
+        default,
+        with = "datetime::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_until: Option<DateTime<Utc>>,
+}
+
+impl FeatureContext {
+    /// Whether the feature context has an `active_from` or `active_until` time
+    pub fn is_scheduled(&self) -> bool {
+        self.active_from.is_some() || self.active_until.is_some()
+    }
+
+    /// Whether the feature context is active at `now`, i.e. in `[active_from, active_until)`
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.active_from
+            .is_none_or(|active_from| active_from <= now)
+            && self
+                .active_until
+                .is_none_or(|active_until| now < active_until)
+    }
+}
+
+/// A feature another feature depends on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FeaturePrerequisite {
+    /// The name of the prerequisite feature.
+    pub feature: String,
+    /// The value the prerequisite flag must evaluate to. Any value is accepted if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<FlagsmithValue>,
 }
 
 /// Represents environment metadata.
@@ -67,6 +118,15 @@ pub struct EnvironmentContext {
     pub key: String,
     /// The environment name.
     pub name: String,
+    /// Whether flags that evaluate to disabled are left out of evaluation results.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hide_disabled_flags: bool,
+    /// Whether the organisation owning the environment has stopped serving flags.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stop_serving_flags: bool,
+    /// Whether the organisation owning the environment collects feature analytics.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub feature_analytics: bool,
 }
 
 /// Represents identity context for feature flag evaluation.
@@ -101,6 +161,31 @@ pub enum ConditionOperator {
     Modulo,
     IsSet,
     IsNotSet,
+    /// Matches when the context is in the segment whose key is the condition value.
+    InSegment,
+    StartsWith,
+    EndsWith,
+    NotIn,
+    /// Like `Equal`, ignoring case.
+    EqualIgnoreCase,
+    /// Like `Contains`, ignoring case.
+    ContainsIgnoreCase,
+    /// Like `In`, ignoring case.
+    InIgnoreCase,
+    /// Matches versions satisfying a `semver::VersionReq`, e.g. `^2.3` or `>=1.2, <2`.
+    SemverRange,
+    /// Matches IP addresses in any of the CIDR blocks of the condition value list,
+    /// e.g. `["10.0.0.0/8", "2001:db8::/32"]`.
+    InCidr,
+    /// Matches list values with at least one item in the condition value list.
+    /// Values that are not lists or objects are lists of one item.
+    ContainsAny,
+    /// Matches list values with every item of the condition value list.
+    /// Values that are not lists or objects are lists of one item.
+    ContainsAll,
+    /// Matches list values with more items than the condition value.
+    /// Values that are not lists or objects are lists of one item.
+    SizeGreaterThan,
 }
 
 /// Represents a condition value that can be either a single string or an array of strings.
@@ -265,3 +350,114 @@ pub struct EngineEvaluationContext {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identity: Option<IdentityContext>,
 }
+
+/// An evaluation context borrowed together with the identity it is evaluated for.
+///
+/// Lets identities be evaluated against a shared context without copying it. A view
+/// lives for a single evaluation, and builds the JSON document arbitrary JSONPath
+/// properties are queried against at most once.
+#[derive(Clone, Debug)]
+pub(crate) struct ContextView<'a> {
+    /// The evaluation context. Its own identity is ignored.
+    pub(crate) context: &'a EngineEvaluationContext,
+    /// The identity to evaluate the context for.
+    pub(crate) identity: Option<&'a IdentityContext>,
+    /// The context serialized with `identity`, built on first use.
+    pub(super) serialized: OnceCell<Option<serde_json::Value>>,
+    /// Compiled segments of the context sorted by key, if it was compiled.
+    pub(crate) compiled_segments: &'a [CompiledSegment],
+    /// Flag results that are the same for every identity, by feature name, if the context
+    /// was compiled.
+    pub(crate) static_flag_results: Option<&'a HashMap<String, FlagResult>>,
+    /// Whether the context is in each segment evaluated so far, by segment key.
+    pub(super) segment_memberships: RefCell<HashMap<&'a str, bool>>,
+    /// Keys of the segments that reference themselves, found the first time a segment
+    /// with IN_SEGMENT conditions is evaluated.
+    pub(super) cyclic_segments: OnceCell<HashSet<&'a str>>,
+    /// The time relative `:datetime` conditions and schedules are compared against, read on first use.
+    pub(crate) now: OnceCell<DateTime<Utc>>,
+}
+
+impl<'a> ContextView<'a> {
+    /// Borrows a context to evaluate it for the given identity
+    pub(crate) fn with_identity(
+        context: &'a EngineEvaluationContext,
+        identity: Option<&'a IdentityContext>,
+    ) -> Self {
+        ContextView {
+            context,
+            identity,
+            serialized: OnceCell::new(),
+            compiled_segments: &[],
+            static_flag_results: None,
+            segment_memberships: RefCell::new(HashMap::new()),
+            cyclic_segments: OnceCell::new(),
+            now: OnceCell::new(),
+        }
+    }
+
+    /// The time of the evaluation, so every condition sees the same time
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        *self.now.get_or_init(Utc::now)
+    }
+
+    /// Uses the compiled segments of a `CompiledContext`, sorted by key
+    pub(crate) fn with_compiled_segments(
+        mut self,
+        compiled_segments: &'a [CompiledSegment],
+    ) -> Self {
+        self.compiled_segments = compiled_segments;
+        self
+    }
+
+    /// Uses the flag results of a `CompiledContext` that are the same for every identity
+    pub(crate) fn with_static_flag_results(
+        mut self,
+        static_flag_results: &'a HashMap<String, FlagResult>,
+    ) -> Self {
+        self.static_flag_results = Some(static_flag_results);
+        self
+    }
+
+    /// The compiled segment stored under the given key, if the context was compiled
+    pub(crate) fn compiled_segment(&self, segment_key: &str) -> Option<&'a CompiledSegment> {
+        let segments = self.compiled_segments;
+        segments
+            .binary_search_by(|segment| segment.key.as_str().cmp(segment_key))
+            .ok()
+            .map(|index| &segments[index])
+    }
+
+    /// Whether the segment stored under the given key references itself through
+    /// IN_SEGMENT conditions
+    pub(crate) fn is_segment_in_cycle(&self, segment_key: &str) -> bool {
+        self.cyclic_segments
+            .get_or_init(|| get_cyclic_segment_keys(self.context))
+            .contains(segment_key)
+    }
+
+    /// The context serialized to JSON with the identity being evaluated, if it serializes
+    pub(crate) fn serialized(&self) -> Option<&serde_json::Value> {
+        self.serialized
+            .get_or_init(|| {
+                let mut context_json = serde_json::to_value(self.context).ok()?;
+                let object = context_json.as_object_mut()?;
+                match self.identity {
+                    Some(identity) => {
+                        object.insert("identity".to_string(), serde_json::to_value(identity).ok()?);
+                    }
+                    None => {
+                        object.remove("identity");
+                    }
+                }
+                Some(context_json)
+            })
+            .as_ref()
+    }
+}
+
+impl<'a> From<&'a EngineEvaluationContext> for ContextView<'a> {
+    fn from(context: &'a EngineEvaluationContext) -> Self {
+        ContextView::with_identity(context, context.identity.as_ref())
+    }
+}
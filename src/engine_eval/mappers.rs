@@ -3,7 +3,9 @@ use super::context::{
     FeatureMetadata, FeatureValue, IdentityContext, SegmentContext, SegmentMetadata, SegmentRule,
     SegmentRuleType, SegmentSource,
 };
+use super::result::IDENTITY_OVERRIDES_SEGMENT_NAME;
 use crate::environments::Environment;
+use crate::error::{Error, ErrorKind};
 use crate::features::{FeatureState, MultivariateFeatureStateValue};
 use crate::identities::{Identity, Trait};
 use crate::segments::{Segment, SegmentRule as OldSegmentRule};
@@ -18,10 +20,40 @@ use std::collections::HashMap;
 /// # Returns
 /// A new engine evaluation context
 pub fn environment_to_context(environment: Environment) -> EngineEvaluationContext {
+    match map_environment(environment, false) {
+        Ok(ctx) => ctx,
+        Err(_) => unreachable!("lenient mapping never fails"),
+    }
+}
+
+/// Maps an Environment to an EngineEvaluationContext, failing instead of falling back
+///
+/// `environment_to_context` maps unknown segment rule types to `ALL` and unknown condition
+/// operators to `EQUAL`; this returns an `ErrorKind::Mapping` error for them instead.
+///
+/// # Arguments
+/// * `environment` - The environment to convert
+///
+/// # Returns
+/// A new engine evaluation context, or the first mapping failure
+pub fn try_environment_to_context(
+    environment: Environment,
+) -> Result<EngineEvaluationContext, Error> {
+    map_environment(environment, true)
+}
+
+/// Maps an Environment, failing on unknown rule types and operators only when `strict` is set
+fn map_environment(
+    environment: Environment,
+    strict: bool,
+) -> Result<EngineEvaluationContext, Error> {
     let mut ctx = EngineEvaluationContext {
         environment: EnvironmentContext {
             key: environment.api_key.clone(),
             name: environment.name.clone(),
+            hide_disabled_flags: environment.project.hide_disabled_flags,
+            stop_serving_flags: environment.project.organisation.stop_serving_flags,
+            feature_analytics: environment.project.organisation.feature_analytics,
         },
         features: HashMap::new(),
         segments: HashMap::new(),
@@ -36,7 +68,7 @@ pub fn environment_to_context(environment: Environment) -> EngineEvaluationConte
 
     // Map project segments to segment contexts
     for segment in &environment.project.segments {
-        let sc = map_segment_to_segment_context(segment);
+        let sc = map_segment_to_segment_context(segment, strict)?;
         ctx.segments.insert(sc.key.clone(), sc);
     }
 
@@ -48,7 +80,7 @@ pub fn environment_to_context(environment: Environment) -> EngineEvaluationConte
         }
     }
 
-    ctx
+    Ok(ctx)
 }
 
 /// Maps a FeatureState to a FeatureContext
@@ -74,6 +106,9 @@ fn map_feature_state_to_feature_context(fs: &FeatureState) -> FeatureContext {
                 .clone()
                 .unwrap_or_else(|| "STANDARD".to_string()),
         },
+        prerequisites: vec![],
+        active_from: None,
+        active_until: None,
     };
 
     // Set priority if this is a segment override
@@ -99,7 +134,10 @@ fn map_multivariate_values_to_variants(
 }
 
 /// Maps a Segment to a SegmentContext
-fn map_segment_to_segment_context(segment: &Segment) -> SegmentContext {
+fn map_segment_to_segment_context(
+    segment: &Segment,
+    strict: bool,
+) -> Result<SegmentContext, Error> {
     let mut sc = SegmentContext {
         key: segment.id.to_string(),
         name: segment.name.clone(),
@@ -118,52 +156,66 @@ fn map_segment_to_segment_context(segment: &Segment) -> SegmentContext {
 
     // Map segment rules
     for rule in &segment.rules {
-        sc.rules.push(map_segment_rule_to_rule(rule));
+        let rule = map_segment_rule_to_rule(rule, strict)
+            .map_err(|error| mapping_error(format!("segment {}: {}", segment.id, error)))?;
+        sc.rules.push(rule);
     }
 
-    sc
+    Ok(sc)
 }
 
 /// Maps a legacy SegmentRule to the new SegmentRule format
-fn map_segment_rule_to_rule(rule: &OldSegmentRule) -> SegmentRule {
-    let rule_type = map_rule_type(&rule.segment_rule_type);
+///
+/// Unknown rule types map to `ALL` and unknown operators to `EQUAL`, unless `strict` is set.
+fn map_segment_rule_to_rule(rule: &OldSegmentRule, strict: bool) -> Result<SegmentRule, String> {
+    let rule_type = match map_rule_type(&rule.segment_rule_type) {
+        Some(rule_type) => rule_type,
+        None if strict => {
+            return Err(format!("unknown rule type {:?}", rule.segment_rule_type));
+        }
+        None => SegmentRuleType::All,
+    };
 
-    let conditions = rule
-        .conditions
-        .iter()
-        .map(|c| Condition {
-            operator: map_operator(&c.operator),
+    let mut conditions = Vec::with_capacity(rule.conditions.len());
+    for c in &rule.conditions {
+        let operator = match map_operator(&c.operator) {
+            Some(operator) => operator,
+            None if strict => return Err(format!("unknown operator {:?}", c.operator)),
+            None => ConditionOperator::Equal,
+        };
+        conditions.push(Condition {
+            operator,
             property: c.property.clone().unwrap_or_default(),
             value: super::context::ConditionValue::Single(c.value.clone().unwrap_or_default()),
-        })
-        .collect();
+        });
+    }
 
     let rules = rule
         .rules
         .iter()
-        .map(|r| map_segment_rule_to_rule(r))
-        .collect();
+        .map(|r| map_segment_rule_to_rule(r, strict))
+        .collect::<Result<_, _>>()?;
 
-    SegmentRule {
+    Ok(SegmentRule {
         rule_type,
         conditions,
         rules,
-    }
+    })
 }
 
 /// Maps a rule type string to SegmentRuleType enum
-fn map_rule_type(rule_type: &str) -> SegmentRuleType {
-    match rule_type {
+fn map_rule_type(rule_type: &str) -> Option<SegmentRuleType> {
+    Some(match rule_type {
         "ALL" => SegmentRuleType::All,
         "ANY" => SegmentRuleType::Any,
         "NONE" => SegmentRuleType::None,
-        _ => SegmentRuleType::All,
-    }
+        _ => return None,
+    })
 }
 
 /// Maps an operator string to ConditionOperator enum
-fn map_operator(operator: &str) -> ConditionOperator {
-    match operator {
+fn map_operator(operator: &str) -> Option<ConditionOperator> {
+    Some(match operator {
         "EQUAL" => ConditionOperator::Equal,
         "NOT_EQUAL" => ConditionOperator::NotEqual,
         "GREATER_THAN" => ConditionOperator::GreaterThan,
@@ -178,8 +230,24 @@ fn map_operator(operator: &str) -> ConditionOperator {
         "MODULO" => ConditionOperator::Modulo,
         "IS_SET" => ConditionOperator::IsSet,
         "IS_NOT_SET" => ConditionOperator::IsNotSet,
-        _ => ConditionOperator::Equal,
-    }
+        "IN_SEGMENT" => ConditionOperator::InSegment,
+        "STARTS_WITH" => ConditionOperator::StartsWith,
+        "ENDS_WITH" => ConditionOperator::EndsWith,
+        "NOT_IN" => ConditionOperator::NotIn,
+        "EQUAL_IGNORE_CASE" => ConditionOperator::EqualIgnoreCase,
+        "CONTAINS_IGNORE_CASE" => ConditionOperator::ContainsIgnoreCase,
+        "IN_IGNORE_CASE" => ConditionOperator::InIgnoreCase,
+        "SEMVER_RANGE" => ConditionOperator::SemverRange,
+        "IN_CIDR" => ConditionOperator::InCidr,
+        "CONTAINS_ANY" => ConditionOperator::ContainsAny,
+        "CONTAINS_ALL" => ConditionOperator::ContainsAll,
+        "SIZE_GREATER_THAN" => ConditionOperator::SizeGreaterThan,
+        _ => return None,
+    })
+}
+
+fn mapping_error(message: String) -> Error {
+    Error::with_message(ErrorKind::Mapping, message)
 }
 
 /// Helper struct for grouping identity overrides
@@ -245,7 +313,7 @@ fn map_identity_overrides_to_segments(identities: &[Identity]) -> HashMap<String
         // Create segment context
         let mut sc = SegmentContext {
             key: String::new(), // Identity override segments never use % Split operator
-            name: "identity_overrides".to_string(),
+            name: IDENTITY_OVERRIDES_SEGMENT_NAME.to_string(),
             metadata: SegmentMetadata {
                 segment_id: None,
                 source: SegmentSource::IdentityOverride,
@@ -276,6 +344,9 @@ fn map_identity_overrides_to_segments(identities: &[Identity]) -> HashMap<String
                     feature_id: override_key.feature_id,
                     feature_type: override_key.feature_type.clone(),
                 },
+                prerequisites: vec![],
+                active_from: None,
+                active_until: None,
             };
 
             sc.overrides.push(feature_override);
@@ -309,6 +380,9 @@ fn generate_hash(overrides: &[OverrideKey]) -> String {
 
 /// Adds identity data to an existing context
 ///
+/// This copies the whole context. To evaluate an identity without copying it, pass the
+/// result of `identity_to_context` to `engine::get_evaluation_result_for_identity` instead.
+///
 /// # Arguments
 /// * `context` - The context to enrich with identity data
 /// * `identifier` - The identity identifier
@@ -322,7 +396,24 @@ pub fn add_identity_to_context(
     traits: &[Trait],
 ) -> EngineEvaluationContext {
     let mut new_context = context.clone();
+    new_context.identity = Some(identity_to_context(context, identifier, traits));
+    new_context
+}
 
+/// Maps identity data to an IdentityContext for the given context
+///
+/// # Arguments
+/// * `context` - The context the identity is evaluated against
+/// * `identifier` - The identity identifier
+/// * `traits` - The identity traits
+///
+/// # Returns
+/// A new identity context
+pub fn identity_to_context(
+    context: &EngineEvaluationContext,
+    identifier: &str,
+    traits: &[Trait],
+) -> IdentityContext {
     // Create traits map
     let mut identity_traits = HashMap::new();
     for trait_obj in traits {
@@ -330,13 +421,10 @@ pub fn add_identity_to_context(
     }
 
     // Create identity context
-    let environment_key = &new_context.environment.key;
-    let identity = IdentityContext {
+    let environment_key = &context.environment.key;
+    IdentityContext {
         identifier: identifier.to_string(),
         key: format!("{}_{}", environment_key, identifier),
         traits: identity_traits,
-    };
-
-    new_context.identity = Some(identity);
-    new_context
+    }
 }
@@ -7,11 +7,37 @@ pub mod result;
 /// Segment evaluator module for evaluating segment rules
 pub mod segment_evaluator;
 
+/// Compiled module containing the pre-processed CompiledContext
+pub mod compiled;
+
+/// Observer module containing evaluation callbacks and built-in observers
+pub mod observer;
+
+/// Options module containing the EvaluationOptions struct
+pub mod options;
+
+/// Trace module containing the evaluation trace types
+pub mod trace;
+
+/// Validation module for checking evaluation contexts
+pub mod validation;
+
 /// Mappers module for converting between old and new types
 pub mod mappers;
 
 // Re-export commonly used types for convenience
-pub use context::{EngineEvaluationContext, FeatureContext, FeatureMetadata, SegmentSource};
-pub use mappers::{add_identity_to_context, environment_to_context};
-pub use result::{EvaluationResult, FlagResult, SegmentResult};
+pub use compiled::CompiledContext;
+pub use context::{
+    EngineEvaluationContext, FeatureContext, FeatureMetadata, FeaturePrerequisite, IdentityContext,
+    SegmentSource,
+};
+pub use mappers::{
+    add_identity_to_context, environment_to_context, identity_to_context,
+    try_environment_to_context,
+};
+pub use observer::{EvaluationCounter, EvaluationObserver};
+pub use options::{Clock, EvaluationOptions, StopServingFlagsBehaviour, SystemClock};
+pub use result::{EvaluationResult, FlagResult, Reason, SegmentResult};
 pub use segment_evaluator::is_context_in_segment;
+pub use trace::EvaluationTrace;
+pub use validation::{ValidationError, ValidationProblem, ValidationProblemKind};
@@ -0,0 +1,142 @@
+use super::context::{FeatureContext, FeatureValue};
+use super::result::{FlagResult, SegmentResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Receives callbacks while a context is evaluated.
+///
+/// All callbacks default to doing nothing, so implementations only override
+/// the ones they need. Observers may be shared between threads evaluating
+/// concurrently.
+pub trait EvaluationObserver: Send + Sync {
+    /// Called when the context matches a segment.
+    fn on_segment_matched(&self, _segment: &SegmentResult) {}
+
+    /// Called when a multivariate variant is selected for a feature.
+    fn on_variant_selected(
+        &self,
+        _feature_context: &FeatureContext,
+        _variant_index: usize,
+        _variant: &FeatureValue,
+    ) {
+    }
+
+    /// Called for every flag result produced.
+    fn on_flag_evaluated(&self, _flag: &FlagResult) {}
+
+    /// Whether the observer records feature analytics, in which case it is only
+    /// notified of flags evaluated with `EnvironmentContext.feature_analytics` set.
+    fn records_feature_analytics(&self) -> bool {
+        false
+    }
+}
+
+/// Evaluation counts of a single feature.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureEvaluationCount {
+    /// Number of times the feature was evaluated.
+    pub total: u64,
+    /// Number of evaluations per reason, keyed by the reason wire string.
+    pub by_reason: HashMap<String, u64>,
+}
+
+/// An observer counting flag evaluations in memory, keyed by `FeatureMetadata.feature_id`.
+///
+/// Evaluations are only counted when `EnvironmentContext.feature_analytics` is set;
+/// periodically `take` the counts to report them.
+#[derive(Debug, Default)]
+pub struct EvaluationCounter {
+    counts: Mutex<HashMap<u32, FeatureEvaluationCount>>,
+}
+
+impl EvaluationCounter {
+    /// Creates a counter with no evaluations counted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the counts of a feature
+    pub fn get(&self, feature_id: u32) -> FeatureEvaluationCount {
+        self.lock().get(&feature_id).cloned().unwrap_or_default()
+    }
+
+    /// Gets the counts of all features evaluated so far
+    pub fn counts(&self) -> HashMap<u32, FeatureEvaluationCount> {
+        self.lock().clone()
+    }
+
+    /// Takes the counts of all features evaluated so far, resetting the counter
+    pub fn take(&self) -> HashMap<u32, FeatureEvaluationCount> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, FeatureEvaluationCount>> {
+        // A panic while holding the lock cannot leave the counts inconsistent
+        self.counts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl EvaluationObserver for EvaluationCounter {
+    fn on_flag_evaluated(&self, flag: &FlagResult) {
+        let mut counts = self.lock();
+        let count = counts.entry(flag.metadata.feature_id).or_default();
+        count.total += 1;
+        *count.by_reason.entry(flag.reason.to_string()).or_default() += 1;
+    }
+
+    fn records_feature_analytics(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_eval::context::FeatureMetadata;
+    use crate::engine_eval::result::Reason;
+    use crate::types::FlagsmithValue;
+
+    fn flag_result(feature_id: u32, reason: Reason) -> FlagResult {
+        FlagResult {
+            enabled: true,
+            name: format!("feature_{}", feature_id),
+            reason,
+            value: FlagsmithValue::default(),
+            metadata: FeatureMetadata {
+                feature_id,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn evaluation_counter_counts_per_feature_and_reason() {
+        let counter = EvaluationCounter::new();
+
+        counter.on_flag_evaluated(&flag_result(1, Reason::Default));
+        counter.on_flag_evaluated(&flag_result(1, Reason::Default));
+        counter.on_flag_evaluated(&flag_result(1, Reason::IdentityOverride));
+        counter.on_flag_evaluated(&flag_result(2, Reason::Default));
+
+        let count = counter.get(1);
+        assert_eq!(count.total, 3);
+        assert_eq!(count.by_reason["DEFAULT"], 2);
+        assert_eq!(
+            count.by_reason["TARGETING_MATCH; segment=identity_overrides"],
+            1
+        );
+        assert_eq!(counter.get(2).total, 1);
+        assert_eq!(counter.get(3), FeatureEvaluationCount::default());
+    }
+
+    #[test]
+    fn evaluation_counter_take_resets_counts() {
+        let counter = EvaluationCounter::new();
+        counter.on_flag_evaluated(&flag_result(1, Reason::Default));
+
+        let counts = counter.take();
+
+        assert_eq!(counts[&1].total, 1);
+        assert!(counter.counts().is_empty());
+    }
+}
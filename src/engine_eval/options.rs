@@ -0,0 +1,76 @@
+use super::observer::EvaluationObserver;
+use chrono::{DateTime, Utc};
+
+/// Source of the current time that relative `:datetime` conditions and the
+/// `active_from` / `active_until` windows of feature contexts are compared against.
+pub trait Clock: Send + Sync {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock reading the system time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A fixed point in time is a clock that never moves.
+impl Clock for DateTime<Utc> {
+    fn now(&self) -> DateTime<Utc> {
+        *self
+    }
+}
+
+/// What evaluation returns for contexts whose organisation stopped serving flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StopServingFlagsBehaviour {
+    /// Return no flags.
+    #[default]
+    Empty,
+    /// Return every flag disabled, with its environment default value.
+    Disabled,
+}
+
+/// Options controlling how a context is evaluated.
+#[derive(Clone, Default)]
+pub struct EvaluationOptions<'a> {
+    /// Observers notified of segment matches, variant selections and flag results.
+    pub observers: Vec<&'a dyn EvaluationObserver>,
+
+    /// What to return when `EnvironmentContext.stop_serving_flags` is set.
+    pub stop_serving_flags: StopServingFlagsBehaviour,
+
+    /// Clock read once per evaluation for relative `:datetime` conditions and schedule
+    /// windows. The system time is used if not set.
+    pub clock: Option<&'a dyn Clock>,
+}
+
+impl<'a> EvaluationOptions<'a> {
+    /// Creates options with no observers, returning no flags when serving stopped and
+    /// reading the system time
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an observer to notify during evaluation
+    pub fn with_observer(mut self, observer: &'a dyn EvaluationObserver) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Sets what to return when the organisation stopped serving flags
+    pub fn with_stop_serving_flags(mut self, behaviour: StopServingFlagsBehaviour) -> Self {
+        self.stop_serving_flags = behaviour;
+        self
+    }
+
+    /// Sets the clock relative `:datetime` conditions and schedule windows are compared against
+    pub fn with_clock(mut self, clock: &'a dyn Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+}
@@ -1,7 +1,11 @@
 use super::context::{FeatureMetadata, SegmentMetadata};
-use crate::types::FlagsmithValue;
-use serde::{Deserialize, Serialize};
+use crate::types::{FlagsmithValue, ValueConversionError};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
+
+/// Name of the segments that identity overrides are mapped to.
+pub(crate) const IDENTITY_OVERRIDES_SEGMENT_NAME: &str = "identity_overrides";
 
 /// Represents the result of a feature flag evaluation.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -23,8 +27,8 @@ pub struct FlagResult {
     /// The name of the feature.
     pub name: String,
 
-    /// The reason for this evaluation result (e.g., "DEFAULT", "TARGETING_MATCH; segment=name", "SPLIT; weight=50").
-    pub reason: String,
+    /// The reason for this evaluation result.
+    pub reason: Reason,
 
     /// The value of the feature flag.
     pub value: FlagsmithValue,
@@ -34,6 +38,24 @@ pub struct FlagResult {
     pub metadata: FeatureMetadata,
 }
 
+impl FlagResult {
+    /// Gets the flag value converted to `T`, e.g. `flag.get_value::<bool>()`
+    pub fn get_value<'a, T>(&'a self) -> Result<T, ValueConversionError>
+    where
+        T: TryFrom<&'a FlagsmithValue, Error = ValueConversionError>,
+    {
+        self.value.get()
+    }
+
+    /// Gets the flag value converted to `T`, or `default` if it cannot be converted
+    pub fn value_or<'a, T>(&'a self, default: T) -> T
+    where
+        T: TryFrom<&'a FlagsmithValue, Error = ValueConversionError>,
+    {
+        self.get_value().unwrap_or(default)
+    }
+}
+
 /// Represents a segment that matched during evaluation.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct SegmentResult {
@@ -44,3 +66,227 @@ pub struct SegmentResult {
     #[serde(default)]
     pub metadata: SegmentMetadata,
 }
+
+/// Explains why a flag evaluated to its value.
+///
+/// Reasons are serialized to the wire strings shared by all Flagsmith engines
+/// (e.g., "DEFAULT", "TARGETING_MATCH; segment=name", "SPLIT; weight=50").
+/// Fields that are not part of the wire string are `None` once deserialized,
+/// and are ignored when comparing reasons.
+#[derive(Clone, Debug)]
+pub enum Reason {
+    /// The environment default of the feature was used.
+    Default,
+    /// The override of a matched segment was used.
+    TargetingMatch {
+        /// The name of the segment.
+        segment_name: String,
+        /// The key of the segment.
+        segment_key: Option<String>,
+    },
+    /// A multivariate variant was selected by percentage split.
+    Split {
+        /// The weight of the selected variant.
+        weight: f64,
+        /// The index of the selected variant, in priority order.
+        variant_index: Option<usize>,
+    },
+    /// An identity override was used.
+    IdentityOverride,
+    /// The organisation stopped serving flags, so the flag is disabled.
+    ServingStopped,
+    /// A prerequisite of the feature is not met, so the flag is disabled.
+    PrerequisiteNotMet {
+        /// The name of the first prerequisite feature that is not met.
+        feature: String,
+    },
+    /// The feature depends on itself through its prerequisites, so the flag is disabled.
+    PrerequisiteCycle,
+    /// The feature is outside its `active_from` / `active_until` window, so the flag is disabled,
+    /// or its segment or identity overrides are, so the environment default is served.
+    Scheduled,
+}
+
+impl PartialEq for Reason {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Reason::Default, Reason::Default) => true,
+            (Reason::IdentityOverride, Reason::IdentityOverride) => true,
+            (Reason::ServingStopped, Reason::ServingStopped) => true,
+            (Reason::PrerequisiteCycle, Reason::PrerequisiteCycle) => true,
+            (Reason::Scheduled, Reason::Scheduled) => true,
+            (
+                Reason::PrerequisiteNotMet { feature },
+                Reason::PrerequisiteNotMet {
+                    feature: other_feature,
+                },
+            ) => feature == other_feature,
+            (
+                Reason::TargetingMatch { segment_name, .. },
+                Reason::TargetingMatch {
+                    segment_name: other_segment_name,
+                    ..
+                },
+            ) => segment_name == other_segment_name,
+            (
+                Reason::Split { weight, .. },
+                Reason::Split {
+                    weight: other_weight,
+                    ..
+                },
+            ) => weight == other_weight,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Default => write!(f, "DEFAULT"),
+            Reason::TargetingMatch { segment_name, .. } => {
+                write!(f, "TARGETING_MATCH; segment={}", segment_name)
+            }
+            Reason::Split { weight, .. } => write!(f, "SPLIT; weight={}", weight),
+            Reason::IdentityOverride => write!(
+                f,
+                "TARGETING_MATCH; segment={}",
+                IDENTITY_OVERRIDES_SEGMENT_NAME
+            ),
+            Reason::ServingStopped => write!(f, "SERVING_STOPPED"),
+            Reason::PrerequisiteNotMet { feature } => {
+                write!(f, "PREREQUISITE_NOT_MET; feature={}", feature)
+            }
+            Reason::PrerequisiteCycle => write!(f, "PREREQUISITE_CYCLE"),
+            Reason::Scheduled => write!(f, "SCHEDULED"),
+        }
+    }
+}
+
+impl Reason {
+    /// Parses a reason from its wire string
+    fn parse(s: &str) -> Option<Reason> {
+        match s {
+            "DEFAULT" => return Some(Reason::Default),
+            "SERVING_STOPPED" => return Some(Reason::ServingStopped),
+            "PREREQUISITE_CYCLE" => return Some(Reason::PrerequisiteCycle),
+            "SCHEDULED" => return Some(Reason::Scheduled),
+            _ => {}
+        }
+
+        if let Some(feature) = s.strip_prefix("PREREQUISITE_NOT_MET; feature=") {
+            return Some(Reason::PrerequisiteNotMet {
+                feature: feature.to_string(),
+            });
+        }
+
+        if let Some(segment_name) = s.strip_prefix("TARGETING_MATCH; segment=") {
+            if segment_name == IDENTITY_OVERRIDES_SEGMENT_NAME {
+                return Some(Reason::IdentityOverride);
+            }
+            return Some(Reason::TargetingMatch {
+                segment_name: segment_name.to_string(),
+                segment_key: None,
+            });
+        }
+
+        if let Some(weight) = s.strip_prefix("SPLIT; weight=") {
+            return weight.parse().ok().map(|weight| Reason::Split {
+                weight,
+                variant_index: None,
+            });
+        }
+
+        None
+    }
+}
+
+impl Serialize for Reason {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Reason {
+    fn deserialize<D>(deserializer: D) -> Result<Reason, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Reason::parse(&s).ok_or_else(|| de::Error::custom(format!("unknown reason: {}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(Reason::Default, "DEFAULT")]
+    #[case(
+        Reason::TargetingMatch { segment_name: "beta users".to_string(), segment_key: Some("1".to_string()) },
+        "TARGETING_MATCH; segment=beta users"
+    )]
+    #[case(Reason::Split { weight: 50.0, variant_index: Some(1) }, "SPLIT; weight=50")]
+    #[case(Reason::Split { weight: 33.3, variant_index: Some(0) }, "SPLIT; weight=33.3")]
+    #[case(
+        Reason::IdentityOverride,
+        "TARGETING_MATCH; segment=identity_overrides"
+    )]
+    #[case(Reason::ServingStopped, "SERVING_STOPPED")]
+    #[case(
+        Reason::PrerequisiteNotMet { feature: "checkout".to_string() },
+        "PREREQUISITE_NOT_MET; feature=checkout"
+    )]
+    #[case(Reason::PrerequisiteCycle, "PREREQUISITE_CYCLE")]
+    #[case(Reason::Scheduled, "SCHEDULED")]
+    fn serialize_deserialize_reason(#[case] reason: Reason, #[case] wire: &str) {
+        let serialized = serde_json::to_value(&reason).unwrap();
+        assert_eq!(serialized, serde_json::json!(wire));
+
+        let deserialized: Reason = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized, reason);
+    }
+
+    #[test]
+    fn deserialize_reason_drops_fields_missing_from_wire() {
+        let reason: Reason = serde_json::from_value(serde_json::json!("SPLIT; weight=50")).unwrap();
+        assert!(matches!(
+            reason,
+            Reason::Split {
+                variant_index: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn deserialize_unknown_reason_fails() {
+        let result = serde_json::from_value::<Reason>(serde_json::json!("SOMETHING_ELSE"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn flag_result_typed_values() {
+        let flag = FlagResult {
+            enabled: true,
+            name: "feature".to_string(),
+            reason: Reason::Default,
+            value: FlagsmithValue::from(10),
+            metadata: FeatureMetadata::default(),
+        };
+
+        assert_eq!(flag.get_value::<i64>(), Ok(10));
+        assert_eq!(flag.value_or(0.0), 10.0);
+        assert!(!flag.value_or(false));
+        assert_eq!(flag.value_or("default".to_string()), "default");
+        assert_eq!(
+            flag.value_or(serde_json::Value::Null),
+            serde_json::json!(10)
+        );
+    }
+}
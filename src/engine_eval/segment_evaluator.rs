@@ -1,22 +1,50 @@
+use super::compiled::{CompiledCondition, CompiledSegment, CompiledSegmentRule};
 use super::context::{
-    Condition, ConditionOperator, EngineEvaluationContext, SegmentContext, SegmentRule,
-    SegmentRuleType,
+    Condition, ConditionOperator, ConditionValue, ContextView, EngineEvaluationContext,
+    SegmentContext, SegmentRule, SegmentRuleType,
 };
-use crate::types::FlagsmithValue;
-use crate::utils::hashing;
-use regex::Regex;
-use semver::Version;
+use super::trace::{ComparisonStrategy, ConditionTrace, RuleTrace};
+use crate::types::{parse_bool, FlagsmithValue};
+use crate::utils::{datetime, hashing};
+use chrono::{DateTime, TimeDelta, Utc};
+use num_bigint::BigInt;
+use regex::{Regex, RegexBuilder};
+use semver::{Version, VersionReq};
 use serde_json_path::JsonPath;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 
 /// Determines if the given evaluation context matches the segment rules
 pub fn is_context_in_segment(ec: &EngineEvaluationContext, segment: &SegmentContext) -> bool {
+    is_context_in_compiled_segment(&ContextView::from(ec), segment, None, None)
+}
+
+/// Determines if the given evaluation context matches the segment rules, using the
+/// pre-parsed rules of a `CompiledContext` when they are provided
+///
+/// Visited rules are recorded into `trace` when one is given.
+pub(crate) fn is_context_in_compiled_segment(
+    ec: &ContextView,
+    segment: &SegmentContext,
+    compiled_rules: Option<&[CompiledSegmentRule]>,
+    mut trace: Option<&mut Vec<RuleTrace>>,
+) -> bool {
     if segment.rules.is_empty() {
         return false;
     }
 
     // All top-level rules must match
-    for rule in &segment.rules {
-        if !context_matches_segment_rule(ec, rule, &segment.key) {
+    for (index, rule) in segment.rules.iter().enumerate() {
+        let compiled_rule = compiled_rules.map(|rules| &rules[index]);
+        if !context_matches_segment_rule(
+            ec,
+            rule,
+            compiled_rule,
+            &segment.key,
+            trace.as_deref_mut(),
+        ) {
             return false;
         }
     }
@@ -24,22 +52,262 @@ pub fn is_context_in_segment(ec: &EngineEvaluationContext, segment: &SegmentCont
     true
 }
 
-/// Checks if the context matches a segment rule
+/// Determines if the context is in the segment stored under `segment_key`
+///
+/// Each segment is evaluated at most once per view, however many `IN_SEGMENT` conditions
+/// reference it. Segments that reference themselves through `IN_SEGMENT` conditions are
+/// never matched, whichever segment is evaluated first, and their rules are not visited.
+/// Rules of other segments are always visited when recorded into `trace`.
+pub(crate) fn is_context_in_keyed_segment<'a>(
+    ec: &ContextView<'a>,
+    segment_key: &'a str,
+    segment: &SegmentContext,
+    compiled_segment: Option<&CompiledSegment>,
+    trace: Option<&mut Vec<RuleTrace>>,
+) -> bool {
+    // Only segments with IN_SEGMENT conditions can reference themselves
+    let in_cycle = match compiled_segment {
+        Some(segment) => segment.in_cycle,
+        None => {
+            segment.rules.iter().any(has_segment_references) && ec.is_segment_in_cycle(segment_key)
+        }
+    };
+    if in_cycle {
+        return false;
+    }
+
+    if trace.is_none() {
+        if let Some(&matched) = ec.segment_memberships.borrow().get(segment_key) {
+            return matched;
+        }
+
+        // Segments that do not depend on the identity were matched at compile time
+        if let Some(matched) = compiled_segment.and_then(|segment| segment.static_match) {
+            return matched;
+        }
+    }
+
+    let compiled_rules = compiled_segment.map(|segment| segment.rules.as_slice());
+    let matched = is_context_in_compiled_segment(ec, segment, compiled_rules, trace);
+    ec.segment_memberships
+        .borrow_mut()
+        .insert(segment_key, matched);
+    matched
+}
+
+/// Checks if a rule or its nested rules have IN_SEGMENT conditions
+pub(crate) fn has_segment_references(rule: &SegmentRule) -> bool {
+    rule.conditions
+        .iter()
+        .any(|condition| condition.operator == ConditionOperator::InSegment)
+        || rule.rules.iter().any(has_segment_references)
+}
+
+/// Gets the keys of the segments that reach themselves through the IN_SEGMENT conditions
+/// of their rules
+///
+/// These are the segments of the strongly connected components of the reference graph
+/// with more than one segment, and the segments referencing themselves, found in a single
+/// pass with Tarjan's algorithm.
+pub(crate) fn get_cyclic_segment_keys(ec: &EngineEvaluationContext) -> HashSet<&str> {
+    /// Search state of a segment visited by Tarjan's algorithm
+    struct Visit {
+        index: usize,
+        low_link: usize,
+        on_stack: bool,
+    }
+
+    struct Search<'a> {
+        references: HashMap<&'a str, Vec<&'a str>>,
+        visits: HashMap<&'a str, Visit>,
+        stack: Vec<&'a str>,
+        cyclic: HashSet<&'a str>,
+    }
+
+    impl<'a> Search<'a> {
+        fn visit(&mut self, segment_key: &'a str) {
+            let index = self.visits.len();
+            self.visits.insert(
+                segment_key,
+                Visit {
+                    index,
+                    low_link: index,
+                    on_stack: true,
+                },
+            );
+            self.stack.push(segment_key);
+
+            let references = self.references[segment_key].clone();
+            for referenced in references {
+                let low_link = match self.visits.get(referenced) {
+                    None => {
+                        self.visit(referenced);
+                        self.visits[referenced].low_link
+                    }
+                    Some(visit) if visit.on_stack => visit.index,
+                    Some(_) => continue,
+                };
+                let visit = self.visits.get_mut(segment_key).unwrap();
+                visit.low_link = visit.low_link.min(low_link);
+            }
+
+            if self.visits[segment_key].low_link != index {
+                return;
+            }
+            let position = self.stack.iter().rposition(|key| *key == segment_key);
+            let component = self.stack.split_off(position.unwrap_or_default());
+            for key in &component {
+                self.visits.get_mut(key).unwrap().on_stack = false;
+            }
+            let references_itself = self.references[segment_key].contains(&segment_key);
+            if component.len() > 1 || references_itself {
+                self.cyclic.extend(component);
+            }
+        }
+    }
+
+    let mut search = Search {
+        references: ec
+            .segments
+            .iter()
+            .map(|(key, sc)| (key.as_str(), referenced_segment_keys(ec, sc)))
+            .collect(),
+        visits: HashMap::new(),
+        stack: vec![],
+        cyclic: HashSet::new(),
+    };
+    for segment_key in ec.segments.keys() {
+        if !search.visits.contains_key(segment_key.as_str()) {
+            search.visit(segment_key);
+        }
+    }
+    search.cyclic
+}
+
+/// Keys of the existing segments referenced by IN_SEGMENT conditions of a segment
+fn referenced_segment_keys<'a>(
+    ec: &'a EngineEvaluationContext,
+    segment: &SegmentContext,
+) -> Vec<&'a str> {
+    fn collect<'a>(ec: &'a EngineEvaluationContext, rule: &SegmentRule, keys: &mut Vec<&'a str>) {
+        for condition in &rule.conditions {
+            if condition.operator == ConditionOperator::InSegment {
+                let referenced_key = match &condition.value {
+                    ConditionValue::Single(key) => Cow::Borrowed(key.as_str()),
+                    value => Cow::Owned(value.as_string()),
+                };
+                if let Some((key, _)) = ec.segments.get_key_value(referenced_key.as_ref()) {
+                    keys.push(key);
+                }
+            }
+        }
+        for nested in &rule.rules {
+            collect(ec, nested, keys);
+        }
+    }
+
+    let mut keys = Vec::new();
+    for rule in &segment.rules {
+        collect(ec, rule, &mut keys);
+    }
+    keys
+}
+
+/// Checks if a rule matches the same way for every identity
+///
+/// Only conditions on environment JSONPath properties qualify, and only when they resolve
+/// without an identity, since unresolved properties fall back to trait lookups.
+/// `IN_SEGMENT` conditions never qualify, as the referenced segment may depend on the identity,
+/// and neither do conditions on datetimes relative to the evaluation time.
+///
+/// `ec` is expected to have no identity.
+pub(crate) fn is_identity_independent_rule(
+    ec: &ContextView,
+    rule: &SegmentRule,
+    compiled_rule: &CompiledSegmentRule,
+) -> bool {
+    let conditions_independent =
+        rule.conditions
+            .iter()
+            .zip(&compiled_rule.conditions)
+            .all(|(condition, compiled)| {
+                let property = condition.property.as_str();
+                let operands = ConditionOperands {
+                    condition,
+                    compiled: Some(compiled),
+                };
+                condition.operator != ConditionOperator::InSegment
+                    && !matches!(compiled.datetime, Some(DatetimeOperand::Relative(_)))
+                    && (property.starts_with("$.environment.")
+                        || property.starts_with("$.environment["))
+                    && get_value_from_path(ec, &operands).is_some()
+            });
+
+    conditions_independent
+        && rule
+            .rules
+            .iter()
+            .zip(&compiled_rule.rules)
+            .all(|(nested_rule, compiled_nested_rule)| {
+                is_identity_independent_rule(ec, nested_rule, compiled_nested_rule)
+            })
+}
+
+/// Checks if the context matches a segment rule, recording it into `trace` when given
 fn context_matches_segment_rule(
-    ec: &EngineEvaluationContext,
+    ec: &ContextView,
+    rule: &SegmentRule,
+    compiled_rule: Option<&CompiledSegmentRule>,
+    segment_key: &str,
+    trace: Option<&mut Vec<RuleTrace>>,
+) -> bool {
+    let Some(trace) = trace else {
+        return rule_matches(ec, rule, compiled_rule, segment_key, None);
+    };
+
+    let mut rule_trace = RuleTrace {
+        rule_type: rule.rule_type.clone(),
+        matched: false,
+        conditions: vec![],
+        rules: vec![],
+    };
+    rule_trace.matched = rule_matches(ec, rule, compiled_rule, segment_key, Some(&mut rule_trace));
+    let matched = rule_trace.matched;
+    trace.push(rule_trace);
+    matched
+}
+
+/// Checks the conditions and nested rules of a segment rule
+fn rule_matches(
+    ec: &ContextView,
     rule: &SegmentRule,
+    compiled_rule: Option<&CompiledSegmentRule>,
     segment_key: &str,
+    mut trace: Option<&mut RuleTrace>,
 ) -> bool {
     // Check conditions if present
     if !rule.conditions.is_empty()
-        && !matches_conditions_by_rule_type(ec, &rule.conditions, &rule.rule_type, segment_key)
+        && !matches_conditions_by_rule_type(
+            ec,
+            rule,
+            compiled_rule,
+            segment_key,
+            trace.as_deref_mut().map(|t| &mut t.conditions),
+        )
     {
         return false;
     }
 
     // Check nested rules
-    for nested_rule in &rule.rules {
-        if !context_matches_segment_rule(ec, nested_rule, segment_key) {
+    for (index, nested_rule) in rule.rules.iter().enumerate() {
+        let compiled_nested_rule = compiled_rule.map(|r| &r.rules[index]);
+        if !context_matches_segment_rule(
+            ec,
+            nested_rule,
+            compiled_nested_rule,
+            segment_key,
+            trace.as_deref_mut().map(|t| &mut t.rules),
+        ) {
             return false;
         }
     }
@@ -49,13 +317,31 @@ fn context_matches_segment_rule(
 
 /// Checks if conditions match according to the rule type
 fn matches_conditions_by_rule_type(
-    ec: &EngineEvaluationContext,
-    conditions: &[Condition],
-    rule_type: &SegmentRuleType,
+    ec: &ContextView,
+    rule: &SegmentRule,
+    compiled_rule: Option<&CompiledSegmentRule>,
     segment_key: &str,
+    mut trace: Option<&mut Vec<ConditionTrace>>,
 ) -> bool {
-    for condition in conditions {
-        let condition_matches = context_matches_condition(ec, condition, segment_key);
+    let rule_type = &rule.rule_type;
+    for (index, condition) in rule.conditions.iter().enumerate() {
+        let operands = ConditionOperands {
+            condition,
+            compiled: compiled_rule.map(|r| &r.conditions[index]),
+        };
+        let evaluation = context_matches_condition(ec, &operands, segment_key);
+        let condition_matches = evaluation.matched;
+
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.push(ConditionTrace {
+                property: condition.property.clone(),
+                operator: condition.operator.clone(),
+                value: condition.value.clone(),
+                context_value: evaluation.context_value,
+                strategy: evaluation.strategy,
+                matched: evaluation.matched,
+            });
+        }
 
         match rule_type {
             SegmentRuleType::All => {
@@ -80,47 +366,523 @@ fn matches_conditions_by_rule_type(
     *rule_type != SegmentRuleType::Any
 }
 
+/// The outcome of evaluating a single condition
+struct ConditionEvaluation {
+    matched: bool,
+    strategy: ComparisonStrategy,
+    context_value: Option<FlagsmithValue>,
+}
+
+/// A condition paired with its pre-parsed operands, if it was compiled.
+/// Operands of conditions that were not compiled are parsed on demand.
+struct ConditionOperands<'a> {
+    condition: &'a Condition,
+    compiled: Option<&'a CompiledCondition>,
+}
+
+impl ConditionOperands<'_> {
+    /// The condition value as a single string
+    fn value(&self) -> Cow<'_, str> {
+        match self.compiled {
+            Some(compiled) => Cow::Borrowed(&compiled.value),
+            None => Cow::Owned(self.condition.value.as_string()),
+        }
+    }
+
+    /// The JSONPath of the condition property, if it is a valid one
+    fn json_path(&self) -> Option<Cow<'_, JsonPath>> {
+        match self.compiled {
+            Some(compiled) => compiled.json_path.as_ref().map(Cow::Borrowed),
+            None => JsonPath::parse(&self.condition.property)
+                .ok()
+                .map(Cow::Owned),
+        }
+    }
+
+    /// The regex of the condition value, if it is a valid pattern
+    fn regex(&self) -> Option<Cow<'_, Regex>> {
+        match self.compiled {
+            Some(compiled) => compiled.regex.as_ref().map(Cow::Borrowed),
+            None => build_regex(&self.condition.value.as_string())
+                .ok()
+                .map(Cow::Owned),
+        }
+    }
+
+    /// The version of a `:semver` suffixed condition value, if it is a valid one
+    fn semver(&self) -> Option<Cow<'_, Version>> {
+        match self.compiled {
+            Some(compiled) => compiled.semver.as_ref().map(Cow::Borrowed),
+            None => parse_semver_condition_value(&self.condition.value.as_string()).map(Cow::Owned),
+        }
+    }
+
+    /// The version requirement of a SEMVER_RANGE condition value, if it is a valid one
+    fn semver_range(&self) -> Option<Cow<'_, VersionReq>> {
+        match self.compiled {
+            Some(compiled) => compiled.semver_range.as_ref().map(Cow::Borrowed),
+            None => parse_semver_range_condition_value(&self.condition.value.as_string())
+                .map(Cow::Owned),
+        }
+    }
+
+    /// The versions of a `:semver` suffixed condition value list, if they are all valid
+    fn semver_list(&self) -> Option<Cow<'_, [Version]>> {
+        match self.compiled {
+            Some(compiled) => compiled.in_versions.as_deref().map(Cow::Borrowed),
+            None => parse_semver_list_condition_value(&self.condition.value).map(Cow::Owned),
+        }
+    }
+
+    /// The version of a `:version` suffixed condition value, if it is a valid one
+    fn version(&self) -> Option<Cow<'_, LooseVersion>> {
+        match self.compiled {
+            Some(compiled) => compiled.version.as_ref().map(Cow::Borrowed),
+            None => {
+                parse_version_condition_value(&self.condition.value.as_string()).map(Cow::Owned)
+            }
+        }
+    }
+
+    /// The CIDR blocks of an IN_CIDR condition value list, if they are all valid
+    fn cidr_blocks(&self) -> Option<Cow<'_, [CidrBlock]>> {
+        match self.compiled {
+            Some(compiled) => compiled.cidr_blocks.as_deref().map(Cow::Borrowed),
+            None => parse_cidr_condition_value(&self.condition.value).map(Cow::Owned),
+        }
+    }
+
+    /// The datetime of a `:datetime` suffixed condition value, if it is a valid one
+    fn datetime(&self) -> Option<DatetimeOperand> {
+        match self.compiled {
+            Some(compiled) => compiled.datetime,
+            None => parse_datetime_condition_value(&self.condition.value.as_string()),
+        }
+    }
+
+    /// Checks if the condition value list contains a string
+    fn contains(&self, search: &str) -> bool {
+        match self.compiled.and_then(|c| c.in_values.as_ref()) {
+            Some(in_values) => in_values.contains(search),
+            None => self.condition.value.contains_string(search),
+        }
+    }
+
+    /// Checks if every string of the condition value list is in `items`
+    fn is_subset_of(&self, items: &HashSet<&str>) -> bool {
+        match self.compiled.and_then(|c| c.in_values.as_ref()) {
+            Some(in_values) => in_values.iter().all(|value| items.contains(value.as_str())),
+            None => self
+                .condition
+                .value
+                .as_vec()
+                .iter()
+                .all(|value| items.contains(value.as_str())),
+        }
+    }
+
+    /// Checks if the condition value list contains a string, ignoring case
+    ///
+    /// Compiled value lists of IN_IGNORE_CASE conditions are expected in lowercase.
+    fn contains_ignore_case(&self, search: &str) -> bool {
+        let search = search.to_lowercase();
+        match self.compiled.and_then(|c| c.in_values.as_ref()) {
+            Some(in_values) => in_values.contains(&search),
+            None => self
+                .condition
+                .value
+                .as_vec()
+                .iter()
+                .any(|value| value.to_lowercase() == search),
+        }
+    }
+}
+
+/// Maximum size of a compiled REGEX condition pattern, in bytes.
+///
+/// This is the default of the regex crate, made explicit so validation can report it.
+pub const REGEX_SIZE_LIMIT: usize = 10 * (1 << 20);
+
+/// Maximum size of the cache used to match a REGEX condition pattern, in bytes.
+///
+/// This only caps the memory used while matching: a pattern never fails to compile
+/// because of it, so validation does not report it, and matching slows down instead
+/// of failing when the cache fills up.
+pub const REGEX_DFA_SIZE_LIMIT: usize = 1 << 20;
+
+/// Compiles the pattern of a REGEX condition value, within the engine regex limits
+///
+/// Patterns can set options with the inline flags the regex crate supports, as there are
+/// no separate condition options, e.g. `(?i)` for case-insensitive matching,
+/// `(?m)` for `^` and `$` matching at line boundaries, `(?s)` for `.` matching new lines,
+/// `(?x)` to ignore whitespace and comments, or `(?U)` for lazy repetitions. Flags can be
+/// combined, like `(?im)`, cleared, like `(?-i)`, or scoped to a group, like `(?i:beta)`.
+///
+/// Patterns compiling to more than `REGEX_SIZE_LIMIT` bytes fail with
+/// `regex::Error::CompiledTooBig`.
+pub(crate) fn build_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+        .build()
+}
+
+/// Parses a condition value of the form `<version>:semver`
+pub(crate) fn parse_semver_condition_value(condition_value: &str) -> Option<Version> {
+    let version_str = condition_value.strip_suffix(":semver")?;
+    Version::parse(version_str).ok()
+}
+
+/// Parses a SEMVER_RANGE condition value, e.g. `^2.3` or `>=1.2, <2`
+///
+/// The value may be suffixed with `:semver` like other version condition values.
+pub(crate) fn parse_semver_range_condition_value(condition_value: &str) -> Option<VersionReq> {
+    let range_str = condition_value
+        .strip_suffix(":semver")
+        .unwrap_or(condition_value);
+    VersionReq::parse(range_str).ok()
+}
+
+/// Parses a condition value list suffixed with `:semver`, e.g. `1.2.0,1.3.0:semver`
+///
+/// Each version of the list may carry its own `:semver` suffix.
+pub(crate) fn parse_semver_list_condition_value(
+    condition_value: &ConditionValue,
+) -> Option<Vec<Version>> {
+    if !condition_value.as_string().ends_with(":semver") {
+        return None;
+    }
+    condition_value
+        .as_vec()
+        .iter()
+        .map(|version_str| {
+            let version_str = version_str.strip_suffix(":semver").unwrap_or(version_str);
+            Version::parse(version_str).ok()
+        })
+        .collect()
+}
+
+/// A version compared loosely, for `:version` suffixed condition values.
+///
+/// Unlike `:semver`, which needs strict SemVer versions such as `1.2.3`, `:version` accepts
+/// versions like `4.12`, `2024.10.1`, `v3` or Android build codes like `4120`.
+///
+/// A version is parsed as follows:
+///
+/// - A leading `v` or `V` is ignored.
+/// - Anything after a `+` is build metadata, and is ignored.
+/// - The release is one or more dot-separated numbers, e.g. `4.12`. Leading zeros are
+///   ignored, so `4.012` is `4.12`.
+/// - The release may be followed by a `-` and a pre-release tag of dot-separated
+///   identifiers made of ASCII letters, digits and `-`, e.g. `2.0-beta.2`.
+///
+/// Versions are compared as follows:
+///
+/// - Releases are compared number by number, padding the shorter one with zeros, so
+///   `4.12` equals `4.12.0` and is lower than `4.12.1`.
+/// - A version with a pre-release tag is lower than the same release without one, so
+///   `2.0-rc.1` is lower than `2.0`.
+/// - Pre-release tags are compared like in SemVer: identifier by identifier, with numeric
+///   identifiers compared as numbers and lower than alphanumeric ones, which are compared
+///   in ASCII order. A tag that is a prefix of another is lower.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LooseVersion {
+    /// The numbers of the release, without trailing zeros.
+    release: Vec<u64>,
+    /// The identifiers of the pre-release tag, empty for a release.
+    pre_release: Vec<PreReleaseIdentifier>,
+}
+
+/// An identifier of a `LooseVersion` pre-release tag.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum PreReleaseIdentifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+impl LooseVersion {
+    /// Parses a version, returning `None` if it does not follow the rules of `LooseVersion`
+    pub fn parse(version: &str) -> Option<LooseVersion> {
+        let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        let version = version
+            .split_once('+')
+            .map_or(version, |(version, _)| version);
+        let (release, pre_release) = match version.split_once('-') {
+            Some((release, pre_release)) => (release, Some(pre_release)),
+            None => (version, None),
+        };
+
+        let mut release = release
+            .split('.')
+            .map(|number| {
+                if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                number.parse::<u64>().ok()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        while release.last() == Some(&0) {
+            release.pop();
+        }
+
+        let pre_release = match pre_release {
+            Some(pre_release) => pre_release
+                .split('.')
+                .map(PreReleaseIdentifier::parse)
+                .collect::<Option<Vec<_>>>()?,
+            None => vec![],
+        };
+
+        Some(LooseVersion {
+            release,
+            pre_release,
+        })
+    }
+}
+
+impl PreReleaseIdentifier {
+    fn parse(identifier: &str) -> Option<PreReleaseIdentifier> {
+        if identifier.is_empty()
+            || !identifier
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        {
+            return None;
+        }
+        if identifier.bytes().all(|b| b.is_ascii_digit()) {
+            return identifier.parse().ok().map(PreReleaseIdentifier::Numeric);
+        }
+        Some(PreReleaseIdentifier::Alphanumeric(identifier.to_string()))
+    }
+}
+
+impl Ord for LooseVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Without trailing zeros, comparing releases as sequences is the same as padding them
+        self.release.cmp(&other.release).then_with(|| {
+            match (self.pre_release.is_empty(), other.pre_release.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre_release.cmp(&other.pre_release),
+            }
+        })
+    }
+}
+
+impl PartialOrd for LooseVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parses a condition value of the form `<version>:version`, see `LooseVersion`
+pub(crate) fn parse_version_condition_value(condition_value: &str) -> Option<LooseVersion> {
+    let version_str = condition_value.strip_suffix(":version")?;
+    LooseVersion::parse(version_str)
+}
+
+/// An IPv4 or IPv6 network, e.g. `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CidrBlock {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl CidrBlock {
+    /// Parses a CIDR block, or a single address without a prefix length
+    pub(crate) fn parse(block: &str) -> Option<CidrBlock> {
+        let (network, prefix_len) = match block.split_once('/') {
+            Some((network, prefix_len)) => (network, Some(prefix_len)),
+            None => (block, None),
+        };
+        let network: IpAddr = network.parse().ok()?;
+        let max_prefix_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) if prefix_len.bytes().all(|b| b.is_ascii_digit()) => prefix_len
+                .parse()
+                .ok()
+                .filter(|len| *len <= max_prefix_len)?,
+            Some(_) => return None,
+            None => max_prefix_len,
+        };
+        Some(CidrBlock {
+            network,
+            prefix_len,
+        })
+    }
+
+    /// Checks if the block contains an address
+    ///
+    /// IPv4-mapped IPv6 addresses, e.g. `::ffff:10.0.0.1`, are in the IPv4 blocks containing
+    /// the IPv4 address.
+    pub(crate) fn contains(&self, address: IpAddr) -> bool {
+        match (self.network, address.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parses the CIDR blocks of an IN_CIDR condition value list
+pub(crate) fn parse_cidr_condition_value(
+    condition_value: &ConditionValue,
+) -> Option<Vec<CidrBlock>> {
+    condition_value
+        .as_vec()
+        .iter()
+        .map(|block| CidrBlock::parse(block))
+        .collect()
+}
+
+/// The datetime of a `:datetime` suffixed condition value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum DatetimeOperand {
+    /// A fixed point in time.
+    Absolute(DateTime<Utc>),
+    /// An offset from the time of the evaluation.
+    Relative(TimeDelta),
+}
+
+impl DatetimeOperand {
+    /// The point in time, for an evaluation taking place at `now`
+    fn resolve(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            DatetimeOperand::Absolute(datetime) => Some(datetime),
+            DatetimeOperand::Relative(offset) => now.checked_add_signed(offset),
+        }
+    }
+}
+
+/// Parses a condition value of the form `<datetime>:datetime`
+///
+/// The datetime is either parsed like `utils::datetime`, or is relative to the time of the
+/// evaluation, e.g. `now`, `now-30d` or `now+12h`. Offsets are in seconds (`s`), minutes
+/// (`m`), hours (`h`), days (`d`) or weeks (`w`).
+pub(crate) fn parse_datetime_condition_value(condition_value: &str) -> Option<DatetimeOperand> {
+    let datetime_str = condition_value.strip_suffix(":datetime")?;
+    match datetime_str.strip_prefix("now") {
+        Some(offset) => parse_datetime_offset(offset).map(DatetimeOperand::Relative),
+        None => datetime::parse(datetime_str)
+            .ok()
+            .map(DatetimeOperand::Absolute),
+    }
+}
+
+/// Parses an offset of the form `[+-]<amount><unit>`, or an empty one
+fn parse_datetime_offset(offset: &str) -> Option<TimeDelta> {
+    if offset.is_empty() {
+        return Some(TimeDelta::zero());
+    }
+
+    let (sign, offset) = match offset.split_at_checked(1)? {
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return None,
+    };
+    let (amount, unit) = offset.split_at_checked(offset.len().checked_sub(1)?)?;
+    if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let seconds_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+
+    let seconds = amount.parse::<i64>().ok()?.checked_mul(seconds_per_unit)?;
+    TimeDelta::try_seconds(sign * seconds)
+}
+
 /// Checks if the context matches a specific condition
 fn context_matches_condition(
-    ec: &EngineEvaluationContext,
-    condition: &Condition,
+    ec: &ContextView,
+    operands: &ConditionOperands,
     segment_key: &str,
-) -> bool {
+) -> ConditionEvaluation {
+    let condition = operands.condition;
     let context_value = if !condition.property.is_empty() {
-        get_context_value(ec, &condition.property)
+        get_context_value(ec, operands)
     } else {
         None
     };
 
-    match condition.operator {
-        ConditionOperator::PercentageSplit => {
-            match_percentage_split(ec, condition, segment_key, context_value.as_ref())
+    let (matched, strategy) = match condition.operator {
+        ConditionOperator::PercentageSplit => (
+            match_percentage_split(ec, operands, segment_key, context_value.as_ref()),
+            ComparisonStrategy::PercentageSplit,
+        ),
+        ConditionOperator::In | ConditionOperator::InIgnoreCase => {
+            match_in_operator(operands, context_value.as_ref())
+        }
+        ConditionOperator::NotIn => {
+            let (matched, strategy) = match_in_operator(operands, context_value.as_ref());
+            (context_value.is_some() && !matched, strategy)
         }
-        ConditionOperator::In => match_in_operator(condition, context_value.as_ref()),
-        ConditionOperator::IsNotSet => context_value.is_none(),
-        ConditionOperator::IsSet => context_value.is_some(),
+        ConditionOperator::SemverRange => (
+            match_semver_range_operator(operands, context_value.as_ref()),
+            ComparisonStrategy::Semver,
+        ),
+        ConditionOperator::InCidr => (
+            match_in_cidr_operator(operands, context_value.as_ref()),
+            ComparisonStrategy::Cidr,
+        ),
+        ConditionOperator::ContainsAny
+        | ConditionOperator::ContainsAll
+        | ConditionOperator::SizeGreaterThan => (
+            match_list_operator(operands, context_value.as_ref()),
+            ComparisonStrategy::List,
+        ),
+        ConditionOperator::InSegment => (
+            match_in_segment_operator(ec, operands),
+            ComparisonStrategy::SegmentMembership,
+        ),
+        ConditionOperator::IsNotSet => (context_value.is_none(), ComparisonStrategy::Presence),
+        ConditionOperator::IsSet => (context_value.is_some(), ComparisonStrategy::Presence),
         _ => {
             if let Some(ref ctx_val) = context_value {
-                parse_and_match(&condition.operator, ctx_val, &condition.value.as_string())
+                parse_and_match(ec, operands, ctx_val)
             } else {
-                false
+                (false, ComparisonStrategy::Skipped)
             }
         }
+    };
+
+    ConditionEvaluation {
+        matched,
+        strategy,
+        context_value,
     }
 }
 
 /// Gets a value from the context by property name or JSONPath
-fn get_context_value(ec: &EngineEvaluationContext, property: &str) -> Option<FlagsmithValue> {
-    // If property starts with $., try to parse it as a JSONPath expression
+fn get_context_value(ec: &ContextView, operands: &ConditionOperands) -> Option<FlagsmithValue> {
+    let property = operands.condition.property.as_str();
+
+    // If property starts with $., try to resolve it as a JSONPath expression
     if property.starts_with("$.") {
-        if let Some(value) = get_value_from_jsonpath(ec, property) {
+        if let Some(value) = get_value_from_path(ec, operands) {
             return Some(value);
         }
-        // If JSONPath parsing fails, fall through to treat it as a trait name
+        // If JSONPath resolution fails, fall through to treat it as a trait name
     }
 
     // Check traits by property name
-    if let Some(ref identity) = ec.identity {
+    if let Some(identity) = ec.identity {
         if let Some(trait_value) = identity.traits.get(property) {
             return Some(trait_value.clone());
         }
@@ -129,22 +891,85 @@ fn get_context_value(ec: &EngineEvaluationContext, property: &str) -> Option<Fla
     None
 }
 
-/// Gets a value from the context using JSONPath
-fn get_value_from_jsonpath(ec: &EngineEvaluationContext, path: &str) -> Option<FlagsmithValue> {
-    // Parse the JSONPath expression
-    let json_path = match JsonPath::parse(path) {
-        Ok(p) => p,
-        Err(_) => return None,
-    };
+/// Context properties resolved directly from the context, without serializing it
+#[derive(Debug, PartialEq)]
+enum ContextProperty<'a> {
+    /// `$.identity.identifier`
+    IdentityIdentifier,
+    /// `$.identity.key`
+    IdentityKey,
+    /// `$.identity.traits.<trait key>`
+    IdentityTrait(&'a str),
+    /// `$.environment.key`
+    EnvironmentKey,
+    /// `$.environment.name`
+    EnvironmentName,
+}
 
-    // Serialize the context to JSON
-    let context_json = match serde_json::to_value(ec) {
-        Ok(v) => v,
-        Err(_) => return None,
-    };
+impl<'a> ContextProperty<'a> {
+    /// Recognises the JSONPath properties that can be resolved directly
+    fn parse(property: &'a str) -> Option<Self> {
+        match property {
+            "$.identity.identifier" => Some(ContextProperty::IdentityIdentifier),
+            "$.identity.key" => Some(ContextProperty::IdentityKey),
+            "$.environment.key" => Some(ContextProperty::EnvironmentKey),
+            "$.environment.name" => Some(ContextProperty::EnvironmentName),
+            _ => property
+                .strip_prefix("$.identity.traits.")
+                .filter(|trait_key| is_member_name_shorthand(trait_key))
+                .map(ContextProperty::IdentityTrait),
+        }
+    }
+
+    /// Resolves the property to the value querying the serialized context would give
+    fn resolve(&self, ec: &ContextView) -> Option<FlagsmithValue> {
+        let string_value = |value: &str| FlagsmithValue {
+            value: value.to_string(),
+            value_type: crate::types::FlagsmithValueType::String,
+        };
 
-    // Query the JSON using the path
-    let result = json_path.query(&context_json);
+        match self {
+            ContextProperty::IdentityIdentifier => ec
+                .identity
+                .map(|identity| string_value(&identity.identifier)),
+            ContextProperty::IdentityKey => ec.identity.map(|identity| string_value(&identity.key)),
+            ContextProperty::IdentityTrait(trait_key) => {
+                let trait_value = ec.identity?.traits.get(*trait_key)?;
+                // Round-trip through JSON so values are normalised like serialized ones
+                json_to_flagsmith_value(&serde_json::to_value(trait_value).ok()?)
+            }
+            ContextProperty::EnvironmentKey => Some(string_value(&ec.context.environment.key)),
+            ContextProperty::EnvironmentName => Some(string_value(&ec.context.environment.name)),
+        }
+    }
+}
+
+/// Checks if a JSONPath member name can be written in dot notation
+fn is_member_name_shorthand(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic() || !c.is_ascii())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric() || !c.is_ascii())
+}
+
+/// Gets a value from the context by a `$.` prefixed property
+///
+/// Common properties are resolved directly; others are parsed as JSONPath and
+/// queried against the serialized context.
+fn get_value_from_path(ec: &ContextView, operands: &ConditionOperands) -> Option<FlagsmithValue> {
+    match ContextProperty::parse(&operands.condition.property) {
+        Some(property) => property.resolve(ec),
+        None => operands
+            .json_path()
+            .and_then(|json_path| get_value_from_jsonpath(ec, &json_path)),
+    }
+}
+
+/// Gets a value from the context using JSONPath
+fn get_value_from_jsonpath(ec: &ContextView, json_path: &JsonPath) -> Option<FlagsmithValue> {
+    // Query the serialized context using the path
+    let result = json_path.query(ec.serialized()?);
 
     // Get the first match (if any)
     let node_list = result.all();
@@ -153,48 +978,42 @@ fn get_value_from_jsonpath(ec: &EngineEvaluationContext, path: &str) -> Option<F
     }
 
     // Extract the value from the first match
-    let value = node_list[0];
+    json_to_flagsmith_value(node_list[0])
+}
 
-    // Convert to FlagsmithValue based on the JSON type
+/// Converts a JSON value to a FlagsmithValue based on its type
+fn json_to_flagsmith_value(value: &serde_json::Value) -> Option<FlagsmithValue> {
     match value {
         serde_json::Value::String(s) => Some(FlagsmithValue {
             value: s.clone(),
             value_type: crate::types::FlagsmithValueType::String,
         }),
-        serde_json::Value::Number(n) => {
-            if n.is_f64() {
-                Some(FlagsmithValue {
-                    value: n.to_string(),
-                    value_type: crate::types::FlagsmithValueType::Float,
-                })
-            } else {
-                Some(FlagsmithValue {
-                    value: n.to_string(),
-                    value_type: crate::types::FlagsmithValueType::Integer,
-                })
-            }
-        }
+        serde_json::Value::Number(n) => Some(FlagsmithValue::number(n)),
         serde_json::Value::Bool(b) => Some(FlagsmithValue {
             value: b.to_string(),
             value_type: crate::types::FlagsmithValueType::Bool,
         }),
-        _ => None,
+        serde_json::Value::Object(_) | serde_json::Value::Array(_) => {
+            Some(FlagsmithValue::json(value))
+        }
+        serde_json::Value::Null => None,
     }
 }
 
 fn match_percentage_split(
-    ec: &EngineEvaluationContext,
-    condition: &Condition,
+    ec: &ContextView,
+    operands: &ConditionOperands,
     segment_key: &str,
     context_value: Option<&FlagsmithValue>,
 ) -> bool {
-    let float_value = match condition.value.as_string().parse::<f64>() {
+    let condition = operands.condition;
+    let float_value = match operands.value().parse::<f64>() {
         Ok(v) => v,
         Err(_) => return false,
     };
 
     let split_key: Option<String> = if condition.property.is_empty() {
-        ec.identity.as_ref().map(|id| id.key.clone())
+        ec.identity.map(|id| id.key.clone())
     } else {
         context_value.map(|v| v.value.clone())
     };
@@ -209,10 +1028,30 @@ fn match_percentage_split(
     (hash_percentage as f64) <= float_value
 }
 
-/// Matches IN operator
-fn match_in_operator(condition: &Condition, context_value: Option<&FlagsmithValue>) -> bool {
-    if context_value.is_none() {
+/// Matches IN_SEGMENT operator, against the segment whose key is the condition value
+fn match_in_segment_operator(ec: &ContextView, operands: &ConditionOperands) -> bool {
+    let Some((segment_key, segment)) = ec.context.segments.get_key_value(operands.value().as_ref())
+    else {
         return false;
+    };
+    is_context_in_keyed_segment(
+        ec,
+        segment_key,
+        segment,
+        ec.compiled_segment(segment_key),
+        None,
+    )
+}
+
+/// Matches IN operator, or IN_IGNORE_CASE
+///
+/// Returns whether the value is in the list and the comparison strategy used.
+fn match_in_operator(
+    operands: &ConditionOperands,
+    context_value: Option<&FlagsmithValue>,
+) -> (bool, ComparisonStrategy) {
+    if context_value.is_none() {
+        return (false, ComparisonStrategy::Membership);
     }
 
     let ctx_value = context_value.unwrap();
@@ -220,52 +1059,243 @@ fn match_in_operator(condition: &Condition, context_value: Option<&FlagsmithValu
     // IN operator only works with string values, not booleans
     use crate::types::FlagsmithValueType;
     if ctx_value.value_type == FlagsmithValueType::Bool {
-        return false;
+        return (false, ComparisonStrategy::Membership);
     }
 
     let trait_value = &ctx_value.value;
 
-    // Use the ConditionValue's contains_string method for simple string matching
-    condition.value.contains_string(trait_value)
+    // Versions are equal if they have the same precedence, so build metadata is ignored
+    if operands.value().ends_with(":semver") {
+        let matched = Version::parse(trait_value).is_ok_and(|trait_version| {
+            operands.semver_list().is_some_and(|versions| {
+                versions
+                    .iter()
+                    .any(|version| version.cmp_precedence(&trait_version).is_eq())
+            })
+        });
+        return (matched, ComparisonStrategy::Semver);
+    }
+
+    // Use the pre-built value set, or the ConditionValue's contains_string method for simple string matching
+    let matched = match operands.condition.operator {
+        ConditionOperator::InIgnoreCase => operands.contains_ignore_case(trait_value),
+        _ => operands.contains(trait_value),
+    };
+    (matched, ComparisonStrategy::Membership)
 }
 
-/// Parses and matches values based on the operator using type-aware strategy
-fn parse_and_match(
+/// Matches SEMVER_RANGE operator
+///
+/// Like Cargo, pre-release versions only satisfy ranges naming a pre-release of the same version.
+fn match_semver_range_operator(
+    operands: &ConditionOperands,
+    context_value: Option<&FlagsmithValue>,
+) -> bool {
+    let Some(Ok(trait_version)) = context_value.map(|value| Version::parse(&value.value)) else {
+        return false;
+    };
+    operands
+        .semver_range()
+        .is_some_and(|range| range.matches(&trait_version))
+}
+
+/// Matches IN_CIDR operator
+fn match_in_cidr_operator(
+    operands: &ConditionOperands,
+    context_value: Option<&FlagsmithValue>,
+) -> bool {
+    let Some(Ok(address)) = context_value.map(|value| value.value.trim().parse::<IpAddr>()) else {
+        return false;
+    };
+    operands
+        .cidr_blocks()
+        .is_some_and(|blocks| blocks.iter().any(|block| block.contains(address)))
+}
+
+/// Matches CONTAINS_ANY, CONTAINS_ALL and SIZE_GREATER_THAN operators
+fn match_list_operator(
+    operands: &ConditionOperands,
+    context_value: Option<&FlagsmithValue>,
+) -> bool {
+    let Some(items) = context_value.and_then(get_list_items) else {
+        return false;
+    };
+
+    match operands.condition.operator {
+        ConditionOperator::ContainsAny => items.iter().any(|item| operands.contains(item)),
+        ConditionOperator::ContainsAll => {
+            operands.is_subset_of(&items.iter().map(Cow::as_ref).collect())
+        }
+        ConditionOperator::SizeGreaterThan => operands
+            .value()
+            .trim()
+            .parse::<usize>()
+            .is_ok_and(|size| items.len() > size),
+        _ => false,
+    }
+}
+
+/// Gets the items of a list value as strings, or the value itself if it is not a list
+///
+/// String items are used as they are, and other items as JSON. Objects and missing values
+/// have no items.
+pub(crate) fn get_list_items(value: &FlagsmithValue) -> Option<Vec<Cow<'_, str>>> {
+    use crate::types::FlagsmithValueType;
+
+    match value.value_type {
+        FlagsmithValueType::Json => match serde_json::from_str(&value.value).ok()? {
+            serde_json::Value::Array(items) => Some(
+                items
+                    .into_iter()
+                    .map(|item| match item {
+                        serde_json::Value::String(s) => Cow::Owned(s),
+                        item => Cow::Owned(item.to_string()),
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        },
+        FlagsmithValueType::None => None,
+        _ => Some(vec![Cow::Borrowed(&value.value)]),
+    }
+}
+
+/// Matches CONTAINS, NOT_CONTAINS and CONTAINS_IGNORE_CASE against the items of a JSON list
+///
+/// Items are compared whole, like CONTAINS_ANY does. Other operators, and JSON objects,
+/// never match.
+fn match_json_value(
     operator: &ConditionOperator,
     trait_value: &FlagsmithValue,
     condition_value: &str,
-) -> bool {
+) -> (bool, ComparisonStrategy) {
+    let Some(items) = get_list_items(trait_value) else {
+        return (false, ComparisonStrategy::Skipped);
+    };
+
+    let matched = match operator {
+        ConditionOperator::Contains => items.iter().any(|item| item == condition_value),
+        ConditionOperator::NotContains => !items.iter().any(|item| item == condition_value),
+        ConditionOperator::ContainsIgnoreCase => {
+            let condition_value = condition_value.to_lowercase();
+            items
+                .iter()
+                .any(|item| item.to_lowercase() == condition_value)
+        }
+        _ => return (false, ComparisonStrategy::Skipped),
+    };
+    (matched, ComparisonStrategy::List)
+}
+
+/// Parses and matches values based on the operator using type-aware strategy
+///
+/// Returns whether the values matched and the comparison strategy used.
+fn parse_and_match(
+    ec: &ContextView,
+    operands: &ConditionOperands,
+    trait_value: &FlagsmithValue,
+) -> (bool, ComparisonStrategy) {
     use crate::types::FlagsmithValueType;
 
+    let operator = &operands.condition.operator;
+    let condition_value = operands.value();
+    let condition_value = condition_value.as_ref();
+
+    // JSON values are never compared as their raw JSON text
+    if trait_value.value_type == FlagsmithValueType::Json {
+        return match_json_value(operator, trait_value, condition_value);
+    }
+
     // Handle special operators that work across all types
     match operator {
-        ConditionOperator::Modulo => return evaluate_modulo(&trait_value.value, condition_value),
-        ConditionOperator::Regex => return evaluate_regex(&trait_value.value, condition_value),
-        ConditionOperator::Contains => return trait_value.value.contains(condition_value),
-        ConditionOperator::NotContains => return !trait_value.value.contains(condition_value),
+        ConditionOperator::Modulo => {
+            return (
+                evaluate_modulo(&trait_value.value, condition_value),
+                ComparisonStrategy::Modulo,
+            )
+        }
+        ConditionOperator::Regex => {
+            let matched = operands
+                .regex()
+                .is_some_and(|re| re.is_match(&trait_value.value));
+            return (matched, ComparisonStrategy::Regex);
+        }
+        ConditionOperator::Contains => {
+            return (
+                trait_value.value.contains(condition_value),
+                ComparisonStrategy::Substring,
+            )
+        }
+        ConditionOperator::NotContains => {
+            return (
+                !trait_value.value.contains(condition_value),
+                ComparisonStrategy::Substring,
+            )
+        }
+        ConditionOperator::StartsWith => {
+            return (
+                trait_value.value.starts_with(condition_value),
+                ComparisonStrategy::Substring,
+            )
+        }
+        ConditionOperator::EndsWith => {
+            return (
+                trait_value.value.ends_with(condition_value),
+                ComparisonStrategy::Substring,
+            )
+        }
+        ConditionOperator::ContainsIgnoreCase => {
+            return (
+                trait_value
+                    .value
+                    .to_lowercase()
+                    .contains(&condition_value.to_lowercase()),
+                ComparisonStrategy::Substring,
+            )
+        }
+        ConditionOperator::EqualIgnoreCase => {
+            return (
+                trait_value.value.to_lowercase() == condition_value.to_lowercase(),
+                ComparisonStrategy::String,
+            )
+        }
         _ => {}
     }
 
-    // Use type-aware strategy based on trait value type
-    match trait_value.value_type {
-        FlagsmithValueType::Bool => compare_bool(operator, &trait_value.value, condition_value),
-        FlagsmithValueType::Integer => {
-            compare_integer(operator, &trait_value.value, condition_value)
-        }
-        FlagsmithValueType::Float => compare_float(operator, &trait_value.value, condition_value),
-        FlagsmithValueType::String => compare_string(operator, &trait_value.value, condition_value),
-        _ => false,
+    // Check for loose version comparison, whatever the trait value type
+    if condition_value.ends_with(":version") {
+        let matched = operands.version().is_some_and(|condition_version| {
+            LooseVersion::parse(&trait_value.value).is_some_and(|trait_version| {
+                dispatch_operator(operator, &trait_version, condition_version.as_ref())
+            })
+        });
+        return (matched, ComparisonStrategy::Version);
     }
-}
 
-/// Parses a boolean string value with optional integer conversion
-/// NOTE: Historical engine behavior - only "1" is treated as true, "0" is NOT treated as false
-fn parse_bool(s: &str, allow_int_conversion: bool) -> Option<bool> {
-    match s.to_lowercase().as_str() {
-        "true" => Some(true),
-        "1" if allow_int_conversion => Some(true),
-        "false" => Some(false),
-        _ => None,
+    // Check for datetime comparison, whatever the trait value type
+    if condition_value.ends_with(":datetime") {
+        let matched = operands.datetime().is_some_and(|condition_datetime| {
+            evaluate_datetime(ec, operator, &trait_value.value, condition_datetime)
+        });
+        return (matched, ComparisonStrategy::Datetime);
+    }
+
+    // Use type-aware strategy based on trait value type
+    match trait_value.value_type {
+        FlagsmithValueType::Bool => (
+            compare_bool(operator, &trait_value.value, condition_value),
+            ComparisonStrategy::Bool,
+        ),
+        FlagsmithValueType::Integer => (
+            compare_integer(operator, &trait_value.value, condition_value),
+            ComparisonStrategy::Integer,
+        ),
+        FlagsmithValueType::Float => (
+            compare_float(operator, &trait_value.value, condition_value),
+            ComparisonStrategy::Float,
+        ),
+        FlagsmithValueType::String => compare_string(operands, &trait_value.value, condition_value),
+        _ => (false, ComparisonStrategy::Skipped),
     }
 }
 
@@ -289,6 +1319,12 @@ fn compare_bool(operator: &ConditionOperator, trait_value: &str, condition_value
 fn compare_integer(operator: &ConditionOperator, trait_value: &str, condition_value: &str) -> bool {
     if let (Ok(i1), Ok(i2)) = (trait_value.parse::<i64>(), condition_value.parse::<i64>()) {
         dispatch_operator(operator, i1, i2)
+    } else if let (Ok(i1), Ok(i2)) = (
+        trait_value.parse::<BigInt>(),
+        condition_value.parse::<BigInt>(),
+    ) {
+        // Integers beyond i64
+        dispatch_operator(operator, i1, i2)
     } else {
         false
     }
@@ -304,13 +1340,21 @@ fn compare_float(operator: &ConditionOperator, trait_value: &str, condition_valu
 }
 
 /// Compares string values, with special handling for semver
-fn compare_string(operator: &ConditionOperator, trait_value: &str, condition_value: &str) -> bool {
+///
+/// Returns whether the values matched and the comparison strategy used.
+fn compare_string(
+    operands: &ConditionOperands,
+    trait_value: &str,
+    condition_value: &str,
+) -> (bool, ComparisonStrategy) {
+    let operator = &operands.condition.operator;
+
     // Check for semver comparison
-    if let Some(version_str) = condition_value.strip_suffix(":semver") {
-        if let Ok(condition_version) = Version::parse(version_str) {
-            return evaluate_semver(operator, trait_value, &condition_version);
-        }
-        return false;
+    if condition_value.ends_with(":semver") {
+        let matched = operands.semver().is_some_and(|condition_version| {
+            evaluate_semver(operator, trait_value, &condition_version)
+        });
+        return (matched, ComparisonStrategy::Semver);
     }
 
     // Try parsing as boolean for string types (strict - no integer conversion)
@@ -318,25 +1362,35 @@ fn compare_string(operator: &ConditionOperator, trait_value: &str, condition_val
         parse_bool(trait_value, false),
         parse_bool(condition_value, false),
     ) {
-        return match operator {
+        let matched = match operator {
             ConditionOperator::Equal => b1 == b2,
             ConditionOperator::NotEqual => b1 != b2,
             _ => false,
         };
+        return (matched, ComparisonStrategy::Bool);
     }
 
     // Try parsing as integer
     if let (Ok(i1), Ok(i2)) = (trait_value.parse::<i64>(), condition_value.parse::<i64>()) {
-        return dispatch_operator(operator, i1, i2);
+        return (
+            dispatch_operator(operator, i1, i2),
+            ComparisonStrategy::Integer,
+        );
     }
 
     // Try parsing as float
     if let (Ok(f1), Ok(f2)) = (trait_value.parse::<f64>(), condition_value.parse::<f64>()) {
-        return dispatch_operator(operator, f1, f2);
+        return (
+            dispatch_operator(operator, f1, f2),
+            ComparisonStrategy::Float,
+        );
     }
 
     // Fall back to string comparison
-    dispatch_operator(operator, trait_value, condition_value)
+    (
+        dispatch_operator(operator, trait_value, condition_value),
+        ComparisonStrategy::String,
+    )
 }
 
 /// Dispatches the operator to the appropriate comparison function
@@ -356,14 +1410,6 @@ fn dispatch_operator<T: PartialOrd + PartialEq>(
     }
 }
 
-/// Evaluates regex matching
-fn evaluate_regex(trait_value: &str, condition_value: &str) -> bool {
-    if let Ok(re) = Regex::new(condition_value) {
-        return re.is_match(trait_value);
-    }
-    false
-}
-
 /// Evaluates modulo operation
 fn evaluate_modulo(trait_value: &str, condition_value: &str) -> bool {
     let values: Vec<&str> = condition_value.split('|').collect();
@@ -391,6 +1437,23 @@ fn evaluate_modulo(trait_value: &str, condition_value: &str) -> bool {
     ((trait_value_float % divisor) - remainder).abs() < EPSILON
 }
 
+/// Evaluates datetime comparisons, parsing the trait value like `utils::datetime`
+fn evaluate_datetime(
+    ec: &ContextView,
+    operator: &ConditionOperator,
+    trait_value: &str,
+    condition_datetime: DatetimeOperand,
+) -> bool {
+    let Ok(trait_datetime) = datetime::parse(trait_value) else {
+        return false;
+    };
+    condition_datetime
+        .resolve(ec.now())
+        .is_some_and(|condition_datetime| {
+            dispatch_operator(operator, trait_datetime, condition_datetime)
+        })
+}
+
 /// Evaluates semantic version comparisons
 fn evaluate_semver(
     operator: &ConditionOperator,
@@ -416,6 +1479,8 @@ fn evaluate_semver(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine_eval::context::ConditionValue;
+    use rstest::*;
 
     #[test]
     fn test_dispatch_operator_integers() {
@@ -425,10 +1490,911 @@ mod tests {
         assert!(!dispatch_operator(&ConditionOperator::GreaterThan, 5, 6));
     }
 
+    #[test]
+    fn test_compare_integer_beyond_i64() {
+        let big = "18446744073709551616";
+        assert!(compare_integer(&ConditionOperator::Equal, big, big));
+        assert!(compare_integer(&ConditionOperator::GreaterThan, big, "1"));
+        assert!(!compare_integer(&ConditionOperator::LessThan, big, "-1"));
+    }
+
     #[test]
     fn test_evaluate_modulo() {
         assert!(evaluate_modulo("2", "2|0"));
         assert!(!evaluate_modulo("3", "2|0"));
         assert!(evaluate_modulo("35.0", "4|3"));
     }
+
+    fn path_context() -> EngineEvaluationContext {
+        serde_json::from_value(serde_json::json!({
+            "environment": {"key": "env_key", "name": "Environment"},
+            "identity": {
+                "identifier": "user",
+                "key": "env_key_user",
+                "traits": {
+                    "plan": "pro",
+                    "age": 30,
+                    "score": 1.50,
+                    "beta": true,
+                    "empty": null,
+                    "config": {"tier": "gold", "regions": ["eu", "us"]}
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("$.identity.identifier", Some(ContextProperty::IdentityIdentifier))]
+    #[case("$.identity.key", Some(ContextProperty::IdentityKey))]
+    #[case("$.identity.traits.plan", Some(ContextProperty::IdentityTrait("plan")))]
+    #[case("$.environment.key", Some(ContextProperty::EnvironmentKey))]
+    #[case("$.environment.name", Some(ContextProperty::EnvironmentName))]
+    #[case("$.identity.traits.my-trait", None)]
+    #[case("$.identity.traits.plan.name", None)]
+    #[case("$.identity.traits['plan']", None)]
+    #[case("$.identity", None)]
+    fn test_context_property_parse(
+        #[case] property: &str,
+        #[case] expected: Option<ContextProperty>,
+    ) {
+        assert_eq!(ContextProperty::parse(property), expected);
+    }
+
+    #[rstest]
+    #[case("$.identity.identifier")]
+    #[case("$.identity.key")]
+    #[case("$.identity.traits.plan")]
+    #[case("$.identity.traits.age")]
+    #[case("$.identity.traits.score")]
+    #[case("$.identity.traits.beta")]
+    #[case("$.identity.traits.empty")]
+    #[case("$.identity.traits.config")]
+    #[case("$.identity.traits.missing")]
+    #[case("$.environment.key")]
+    #[case("$.environment.name")]
+    fn test_context_property_resolves_like_jsonpath(#[case] property: &str) {
+        let context = path_context();
+        let ec = ContextView::from(&context);
+        let json_path = JsonPath::parse(property).unwrap();
+
+        let direct = ContextProperty::parse(property).unwrap().resolve(&ec);
+
+        assert_eq!(direct, get_value_from_jsonpath(&ec, &json_path));
+
+        let anonymous_ec = ContextView::with_identity(&context, None);
+        assert_eq!(
+            ContextProperty::parse(property)
+                .unwrap()
+                .resolve(&anonymous_ec),
+            get_value_from_jsonpath(&anonymous_ec, &json_path)
+        );
+    }
+
+    #[test]
+    fn test_get_context_value_serializes_context_only_for_other_paths() {
+        let context = path_context();
+        let ec = ContextView::from(&context);
+        let condition = |property: &str| Condition {
+            operator: ConditionOperator::Equal,
+            property: property.to_string(),
+            value: ConditionValue::Single("".to_string()),
+        };
+
+        let identifier = condition("$.identity.identifier");
+        let operands = ConditionOperands {
+            condition: &identifier,
+            compiled: None,
+        };
+        assert_eq!(get_context_value(&ec, &operands).unwrap().value, "user");
+        assert!(ec.serialized.get().is_none());
+
+        let quoted_trait = condition("$.identity.traits['plan']");
+        let operands = ConditionOperands {
+            condition: &quoted_trait,
+            compiled: None,
+        };
+        assert_eq!(get_context_value(&ec, &operands).unwrap().value, "pro");
+        assert!(ec.serialized.get().is_some());
+    }
+
+    fn in_segment_context() -> EngineEvaluationContext {
+        let segment = |conditions: serde_json::Value| {
+            serde_json::json!({
+                "key": "",
+                "name": "segment",
+                "rules": [{"type": "ALL", "conditions": conditions}]
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "environment": {"key": "env_key", "name": "Environment"},
+            "identity": {
+                "identifier": "user",
+                "key": "env_key_user",
+                "traits": {"email": "user@example.com", "beta": true}
+            },
+            "segments": {
+                "staff": segment(serde_json::json!([
+                    {"operator": "REGEX", "property": "email", "value": ".*@example\\.com$"}
+                ])),
+                "staff_beta": segment(serde_json::json!([
+                    {"operator": "IN_SEGMENT", "property": "", "value": "staff"},
+                    {"operator": "EQUAL", "property": "beta", "value": "true"}
+                ])),
+                "not_staff": {
+                    "key": "",
+                    "name": "segment",
+                    "rules": [{
+                        "type": "NONE",
+                        "conditions": [{"operator": "IN_SEGMENT", "property": "", "value": "staff"}]
+                    }]
+                },
+                "unknown": segment(serde_json::json!([
+                    {"operator": "IN_SEGMENT", "property": "", "value": "missing"}
+                ])),
+                "cycle_a": segment(serde_json::json!([
+                    {"operator": "IN_SEGMENT", "property": "", "value": "cycle_b"}
+                ])),
+                "cycle_b": segment(serde_json::json!([
+                    {"operator": "IN_SEGMENT", "property": "", "value": "cycle_a"}
+                ]))
+            }
+        }))
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("staff", true)]
+    #[case("staff_beta", true)]
+    #[case("not_staff", false)]
+    #[case("unknown", false)]
+    #[case("cycle_a", false)]
+    fn test_in_segment_operator(#[case] segment_key: &str, #[case] expected: bool) {
+        let context = in_segment_context();
+        let ec = ContextView::from(&context);
+        let (segment_key, segment) = context.segments.get_key_value(segment_key).unwrap();
+
+        assert_eq!(
+            is_context_in_keyed_segment(&ec, segment_key, segment, None, None),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_in_segment_operator_memoises_referenced_segments() {
+        let context = in_segment_context();
+        let ec = ContextView::from(&context);
+        let segment = &context.segments["staff_beta"];
+
+        assert!(is_context_in_keyed_segment(
+            &ec,
+            "staff_beta",
+            segment,
+            None,
+            None
+        ));
+        assert_eq!(ec.segment_memberships.borrow().get("staff"), Some(&true));
+
+        // Later references use the memoised membership instead of evaluating the segment
+        let mut memberships = ec.segment_memberships.borrow_mut();
+        memberships.remove("staff_beta");
+        memberships.insert("staff", false);
+        drop(memberships);
+        assert!(!is_context_in_keyed_segment(
+            &ec,
+            "staff_beta",
+            segment,
+            None,
+            None
+        ));
+    }
+
+    #[test]
+    fn test_in_segment_operator_cycle_does_not_match() {
+        let context = in_segment_context();
+        let ec = ContextView::from(&context);
+
+        assert!(!is_context_in_segment(
+            &context,
+            &context.segments["cycle_b"]
+        ));
+        assert!(!is_context_in_keyed_segment(
+            &ec,
+            "cycle_b",
+            &context.segments["cycle_b"],
+            None,
+            None
+        ));
+        // Segments in a cycle are not evaluated, so they are not memoised either
+        assert!(ec.segment_memberships.borrow().is_empty());
+        assert_eq!(
+            get_cyclic_segment_keys(&context),
+            HashSet::from(["cycle_a", "cycle_b"])
+        );
+    }
+
+    #[test]
+    fn test_get_cyclic_segment_keys_finds_every_segment_of_a_cycle() {
+        let segment = |references: &[&str]| {
+            let conditions: Vec<_> = references
+                .iter()
+                .map(|key| serde_json::json!({"operator": "IN_SEGMENT", "property": "", "value": key}))
+                .collect();
+            serde_json::json!({"key": "", "name": "segment", "rules": [{"type": "ALL", "conditions": conditions}]})
+        };
+        let context: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "env_key", "name": "Environment"},
+            "segments": {
+                "a": segment(&["b", "c"]),
+                "b": segment(&["a"]),
+                "c": segment(&["b"]),
+                "self": segment(&["self"]),
+                "outside": segment(&["a", "missing"])
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            get_cyclic_segment_keys(&context),
+            HashSet::from(["a", "b", "c", "self"])
+        );
+    }
+
+    #[test]
+    fn test_segments_without_references_skip_cycle_detection() {
+        let context = in_segment_context();
+        let ec = ContextView::from(&context);
+
+        assert!(is_context_in_keyed_segment(
+            &ec,
+            "staff",
+            &context.segments["staff"],
+            None,
+            None
+        ));
+        assert!(ec.cyclic_segments.get().is_none());
+
+        assert!(is_context_in_keyed_segment(
+            &ec,
+            "staff_beta",
+            &context.segments["staff_beta"],
+            None,
+            None
+        ));
+        assert!(ec.cyclic_segments.get().is_some());
+    }
+
+    fn datetime(s: &str) -> DateTime<Utc> {
+        datetime::parse(s).unwrap()
+    }
+
+    #[rstest]
+    #[case(
+        "2024-03-01T12:00:00Z:datetime",
+        Some(DatetimeOperand::Absolute(datetime("2024-03-01T12:00:00Z")))
+    )]
+    #[case(
+        "2024-03-01T12:00:00:datetime",
+        Some(DatetimeOperand::Absolute(datetime("2024-03-01T12:00:00Z")))
+    )]
+    #[case("now:datetime", Some(DatetimeOperand::Relative(TimeDelta::zero())))]
+    #[case("now-30d:datetime", Some(DatetimeOperand::Relative(TimeDelta::days(-30))))]
+    #[case(
+        "now+12h:datetime",
+        Some(DatetimeOperand::Relative(TimeDelta::hours(12)))
+    )]
+    #[case("now-2w:datetime", Some(DatetimeOperand::Relative(TimeDelta::weeks(-2))))]
+    #[case("now-90m:datetime", Some(DatetimeOperand::Relative(TimeDelta::minutes(-90))))]
+    #[case(
+        "now+5s:datetime",
+        Some(DatetimeOperand::Relative(TimeDelta::seconds(5)))
+    )]
+    #[case("now-30:datetime", None)]
+    #[case("now--30d:datetime", None)]
+    #[case("now30d:datetime", None)]
+    #[case("now-30y:datetime", None)]
+    #[case("now-99999999999999999w:datetime", None)]
+    #[case("yesterday:datetime", None)]
+    #[case("2024-03-01T12:00:00Z", None)]
+    fn test_parse_datetime_condition_value(
+        #[case] condition_value: &str,
+        #[case] expected: Option<DatetimeOperand>,
+    ) {
+        assert_eq!(parse_datetime_condition_value(condition_value), expected);
+    }
+
+    #[rstest]
+    #[case(ConditionOperator::LessThan, "2024-01-01T00:00:00Z:datetime", true)]
+    #[case(ConditionOperator::GreaterThan, "2024-01-01T00:00:00Z:datetime", false)]
+    #[case(ConditionOperator::Equal, "2023-12-01T10:00:00+00:00:datetime", true)]
+    #[case(ConditionOperator::Equal, "2023-12-01T11:00:00+01:00:datetime", true)]
+    #[case(ConditionOperator::NotEqual, "2023-12-01T10:00:00Z:datetime", false)]
+    // Signed up more than 30 days before 2024-03-01
+    #[case(ConditionOperator::LessThan, "now-30d:datetime", true)]
+    #[case(ConditionOperator::LessThan, "now-100d:datetime", false)]
+    #[case(ConditionOperator::GreaterThanInclusive, "now-100d:datetime", true)]
+    #[case(ConditionOperator::LessThan, "not a date:datetime", false)]
+    fn test_datetime_conditions(
+        #[case] operator: ConditionOperator,
+        #[case] value: &str,
+        #[case] expected: bool,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity.traits.insert(
+            "signup_date".to_string(),
+            FlagsmithValue::string("2023-12-01T10:00:00"),
+        );
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        ec.now.set(datetime("2024-03-01T00:00:00Z")).unwrap();
+        let condition = Condition {
+            operator,
+            property: "signup_date".to_string(),
+            value: ConditionValue::Single(value.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::Datetime);
+    }
+
+    #[rstest]
+    #[case(ConditionOperator::StartsWith, "user@", true)]
+    #[case(ConditionOperator::StartsWith, "@example.com", false)]
+    #[case(ConditionOperator::EndsWith, "@example.com", true)]
+    #[case(ConditionOperator::EndsWith, "@EXAMPLE.COM", false)]
+    #[case(ConditionOperator::EqualIgnoreCase, "USER@Example.com", true)]
+    #[case(ConditionOperator::EqualIgnoreCase, "user@example.org", false)]
+    #[case(ConditionOperator::ContainsIgnoreCase, "EXAMPLE", true)]
+    #[case(ConditionOperator::ContainsIgnoreCase, "sample", false)]
+    #[case(ConditionOperator::In, "User@Example.com", false)]
+    #[case(
+        ConditionOperator::InIgnoreCase,
+        "admin@example.com,User@Example.com",
+        true
+    )]
+    #[case(ConditionOperator::InIgnoreCase, "admin@example.com", false)]
+    #[case(ConditionOperator::NotIn, "admin@example.com,other@example.com", true)]
+    #[case(ConditionOperator::NotIn, "admin@example.com,user@example.com", false)]
+    fn test_string_conditions(
+        #[case] operator: ConditionOperator,
+        #[case] value: &str,
+        #[case] expected: bool,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity.traits.insert(
+            "email".to_string(),
+            FlagsmithValue::string("user@example.com"),
+        );
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator,
+            property: "email".to_string(),
+            value: ConditionValue::Single(value.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+    }
+
+    #[test]
+    fn test_not_in_condition_does_not_match_missing_value() {
+        let context = path_context();
+        let ec = ContextView::from(&context);
+        let condition = Condition {
+            operator: ConditionOperator::NotIn,
+            property: "email".to_string(),
+            value: ConditionValue::Multiple(vec!["user@example.com".to_string()]),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert!(!evaluation.matched);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::Membership);
+    }
+
+    #[rstest]
+    #[case(ConditionOperator::SemverRange, "^2.3", true)]
+    #[case(ConditionOperator::SemverRange, "^2.3:semver", true)]
+    #[case(ConditionOperator::SemverRange, "~2.4", false)]
+    #[case(ConditionOperator::SemverRange, ">=1.2, <2", false)]
+    #[case(ConditionOperator::SemverRange, ">=2.0.0, <2.3.5", true)]
+    #[case(ConditionOperator::SemverRange, "not a range", false)]
+    #[case(ConditionOperator::In, "2.3.4,3.0.0:semver", true)]
+    #[case(ConditionOperator::In, "2.3.4+build.7:semver", true)]
+    #[case(ConditionOperator::In, "2.3.4:semver,2.4.0:semver", true)]
+    #[case(ConditionOperator::In, "2.3.5,2.4.0:semver", false)]
+    #[case(ConditionOperator::In, "2.3.4,latest:semver", false)]
+    #[case(ConditionOperator::NotIn, "2.4.0-beta:semver", true)]
+    #[case(ConditionOperator::NotIn, "2.3.4+build.7:semver", false)]
+    fn test_semver_list_and_range_conditions(
+        #[case] operator: ConditionOperator,
+        #[case] value: &str,
+        #[case] expected: bool,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity
+            .traits
+            .insert("app_version".to_string(), FlagsmithValue::string("2.3.4"));
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator,
+            property: "app_version".to_string(),
+            value: ConditionValue::Single(value.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::Semver);
+    }
+
+    #[rstest]
+    #[case("2.4.0-beta", "^2.3", false)]
+    #[case("2.4.0-beta", ">=2.4.0-alpha, <2.4.0", true)]
+    #[case("2.4.0", "^2.3", true)]
+    fn test_semver_range_condition_pre_releases(
+        #[case] version: &str,
+        #[case] range: &str,
+        #[case] expected: bool,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity
+            .traits
+            .insert("app_version".to_string(), FlagsmithValue::string(version));
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator: ConditionOperator::SemverRange,
+            property: "app_version".to_string(),
+            value: ConditionValue::Single(range.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        assert_eq!(
+            context_matches_condition(&ec, &operands, "segment").matched,
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("4.12", "4.12.0", Ordering::Equal)]
+    #[case("4.012", "4.12", Ordering::Equal)]
+    #[case("v4.12", "4.12", Ordering::Equal)]
+    #[case("4.12+build.5", "4.12", Ordering::Equal)]
+    #[case("4.12", "4.12.1", Ordering::Less)]
+    #[case("4.9", "4.12", Ordering::Less)]
+    #[case("2024.10.1", "2024.9.30", Ordering::Greater)]
+    #[case("4120", "4119", Ordering::Greater)]
+    #[case("2.0-rc.1", "2.0", Ordering::Less)]
+    #[case("2.0-rc.1", "1.9", Ordering::Greater)]
+    #[case("2.0-alpha", "2.0-alpha.1", Ordering::Less)]
+    #[case("2.0-alpha.2", "2.0-alpha.10", Ordering::Less)]
+    #[case("2.0-10", "2.0-alpha", Ordering::Less)]
+    #[case("2.0-beta", "2.0-alpha", Ordering::Greater)]
+    #[case("1.0.0-rc.01", "1.0.0-rc.1", Ordering::Equal)]
+    fn test_loose_version_ordering(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        let a = LooseVersion::parse(a).unwrap();
+        let b = LooseVersion::parse(b).unwrap();
+
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(a == b, expected == Ordering::Equal);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("v")]
+    #[case("4..12")]
+    #[case("4.12.")]
+    #[case("4.12b")]
+    #[case("four")]
+    #[case("1.0-")]
+    #[case("1.0-rc..1")]
+    #[case("1.0-rc_1")]
+    #[case("99999999999999999999")]
+    fn test_loose_version_rejects_invalid_versions(#[case] version: &str) {
+        assert_eq!(LooseVersion::parse(version), None);
+    }
+
+    #[rstest]
+    #[case(
+        ConditionOperator::Equal,
+        FlagsmithValue::string("4.12"),
+        "4.12.0:version",
+        true
+    )]
+    #[case(
+        ConditionOperator::GreaterThan,
+        FlagsmithValue::string("4.12"),
+        "4.9:version",
+        true
+    )]
+    #[case(
+        ConditionOperator::LessThan,
+        FlagsmithValue::string("2024.10.1"),
+        "2024.10.1-rc.1:version",
+        false
+    )]
+    #[case(
+        ConditionOperator::GreaterThanInclusive,
+        FlagsmithValue::integer(4120),
+        "4120:version",
+        true
+    )]
+    #[case(
+        ConditionOperator::NotEqual,
+        FlagsmithValue::string("v3"),
+        "3.0:version",
+        false
+    )]
+    #[case(
+        ConditionOperator::Equal,
+        FlagsmithValue::string("not a version"),
+        "1:version",
+        false
+    )]
+    #[case(
+        ConditionOperator::Equal,
+        FlagsmithValue::string("1"),
+        "1..0:version",
+        false
+    )]
+    #[case(
+        ConditionOperator::Contains,
+        FlagsmithValue::string("4.12:version"),
+        "4.12:version",
+        true
+    )]
+    fn test_version_conditions(
+        #[case] operator: ConditionOperator,
+        #[case] trait_value: FlagsmithValue,
+        #[case] value: &str,
+        #[case] expected: bool,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity
+            .traits
+            .insert("app_version".to_string(), trait_value);
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator,
+            property: "app_version".to_string(),
+            value: ConditionValue::Single(value.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+    }
+
+    #[rstest]
+    #[case("10.0.0.0/8", "10.255.0.1", true)]
+    #[case("10.0.0.0/8", "11.0.0.1", false)]
+    #[case("10.1.2.3/8", "10.200.0.1", true)]
+    #[case("192.168.1.1", "192.168.1.1", true)]
+    #[case("192.168.1.1", "192.168.1.2", false)]
+    #[case("0.0.0.0/0", "8.8.8.8", true)]
+    #[case("10.0.0.0/8", "::ffff:10.0.0.1", true)]
+    #[case("10.0.0.0/8", "2001:db8::1", false)]
+    #[case("2001:db8::/32", "2001:db8:ffff::1", true)]
+    #[case("2001:db8::/32", "2001:db9::1", false)]
+    #[case("::/0", "::1", true)]
+    #[case("2001:db8::/32", "10.0.0.1", false)]
+    fn test_cidr_block_contains(
+        #[case] block: &str,
+        #[case] address: &str,
+        #[case] expected: bool,
+    ) {
+        let block = CidrBlock::parse(block).unwrap();
+
+        assert_eq!(block.contains(address.parse().unwrap()), expected);
+    }
+
+    #[rstest]
+    #[case("office")]
+    #[case("10.0.0.0/")]
+    #[case("10.0.0.0/33")]
+    #[case("10.0.0.0/+8")]
+    #[case("2001:db8::/129")]
+    #[case("10.0.0/8")]
+    fn test_cidr_block_rejects_invalid_blocks(#[case] block: &str) {
+        assert_eq!(CidrBlock::parse(block), None);
+    }
+
+    #[rstest]
+    #[case(FlagsmithValue::string("10.20.30.40"), true)]
+    #[case(FlagsmithValue::string("2001:db8::42"), true)]
+    #[case(FlagsmithValue::string("192.168.0.1"), false)]
+    #[case(FlagsmithValue::string("not an address"), false)]
+    #[case(FlagsmithValue::integer(10), false)]
+    fn test_in_cidr_conditions(#[case] trait_value: FlagsmithValue, #[case] expected: bool) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity
+            .traits
+            .insert("ip_address".to_string(), trait_value);
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator: ConditionOperator::InCidr,
+            property: "ip_address".to_string(),
+            value: ConditionValue::Multiple(vec![
+                "10.0.0.0/8".to_string(),
+                "2001:db8::/32".to_string(),
+            ]),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::Cidr);
+    }
+
+    #[test]
+    fn test_in_cidr_condition_with_invalid_block_does_not_match() {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity
+            .traits
+            .insert("ip_address".to_string(), FlagsmithValue::string("10.0.0.1"));
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator: ConditionOperator::InCidr,
+            property: "ip_address".to_string(),
+            value: ConditionValue::Single("10.0.0.0/8,office".to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        assert!(!context_matches_condition(&ec, &operands, "segment").matched);
+    }
+
+    #[rstest]
+    #[case(ConditionOperator::ContainsAny, FlagsmithValue::list(["admin", "editor"]), "viewer,editor", true)]
+    #[case(ConditionOperator::ContainsAny, FlagsmithValue::list(["admin", "editor"]), "edit,viewer", false)]
+    #[case(
+        ConditionOperator::ContainsAny,
+        FlagsmithValue::string("admin,editor"),
+        "admin",
+        false
+    )]
+    #[case(
+        ConditionOperator::ContainsAny,
+        FlagsmithValue::string("admin"),
+        "admin,editor",
+        true
+    )]
+    #[case(ConditionOperator::ContainsAny, FlagsmithValue::list([1, 2, 3]), "3,4", true)]
+    #[case(ConditionOperator::ContainsAll, FlagsmithValue::list(["admin", "editor"]), "editor,admin", true)]
+    #[case(ConditionOperator::ContainsAll, FlagsmithValue::list(["admin", "editor"]), "admin,viewer", false)]
+    #[case(ConditionOperator::ContainsAll, FlagsmithValue::list([true, false]), "true", true)]
+    #[case(ConditionOperator::SizeGreaterThan, FlagsmithValue::list(["a", "b", "c"]), "2", true)]
+    #[case(ConditionOperator::SizeGreaterThan, FlagsmithValue::list(["a", "b"]), "2", false)]
+    #[case(ConditionOperator::SizeGreaterThan, FlagsmithValue::list(Vec::<String>::new()), "0", false)]
+    #[case(
+        ConditionOperator::SizeGreaterThan,
+        FlagsmithValue::string("a"),
+        "0",
+        true
+    )]
+    #[case(ConditionOperator::SizeGreaterThan, FlagsmithValue::list(["a"]), "many", false)]
+    #[case(ConditionOperator::ContainsAny, FlagsmithValue::json(&serde_json::json!({"admin": true})), "admin", false)]
+    fn test_list_conditions(
+        #[case] operator: ConditionOperator,
+        #[case] trait_value: FlagsmithValue,
+        #[case] value: &str,
+        #[case] expected: bool,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity.traits.insert("roles".to_string(), trait_value);
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator,
+            property: "roles".to_string(),
+            value: ConditionValue::Single(value.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::List);
+    }
+
+    #[rstest]
+    #[case(ConditionOperator::Contains, "admin", true, ComparisonStrategy::List)]
+    #[case(ConditionOperator::Contains, "dmi", false, ComparisonStrategy::List)]
+    #[case(ConditionOperator::Contains, "\",", false, ComparisonStrategy::List)]
+    #[case(
+        ConditionOperator::NotContains,
+        "admin",
+        false,
+        ComparisonStrategy::List
+    )]
+    #[case(ConditionOperator::NotContains, "dmi", true, ComparisonStrategy::List)]
+    #[case(
+        ConditionOperator::ContainsIgnoreCase,
+        "ADMIN",
+        true,
+        ComparisonStrategy::List
+    )]
+    #[case(
+        ConditionOperator::ContainsIgnoreCase,
+        "DMI",
+        false,
+        ComparisonStrategy::List
+    )]
+    #[case(
+        ConditionOperator::StartsWith,
+        "[\"admin",
+        false,
+        ComparisonStrategy::Skipped
+    )]
+    #[case(ConditionOperator::EndsWith, "]", false, ComparisonStrategy::Skipped)]
+    #[case(ConditionOperator::Regex, "admin", false, ComparisonStrategy::Skipped)]
+    #[case(ConditionOperator::Modulo, "2|0", false, ComparisonStrategy::Skipped)]
+    fn test_string_conditions_on_list_traits(
+        #[case] operator: ConditionOperator,
+        #[case] value: &str,
+        #[case] expected: bool,
+        #[case] strategy: ComparisonStrategy,
+    ) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity.traits.insert(
+            "roles".to_string(),
+            FlagsmithValue::list(["admin", "editor"]),
+        );
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator,
+            property: "roles".to_string(),
+            value: ConditionValue::Single(value.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+        assert_eq!(evaluation.strategy, strategy);
+    }
+
+    #[rstest]
+    #[case(ConditionOperator::Contains)]
+    #[case(ConditionOperator::NotContains)]
+    fn test_string_conditions_on_object_traits_do_not_match(#[case] operator: ConditionOperator) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity.traits.insert(
+            "roles".to_string(),
+            FlagsmithValue::json(&serde_json::json!({"admin": true})),
+        );
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator,
+            property: "roles".to_string(),
+            value: ConditionValue::Single("admin".to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert!(!evaluation.matched);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::Skipped);
+    }
+
+    #[test]
+    fn test_list_conditions_on_jsonpath_traits() {
+        let context: EngineEvaluationContext = serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "identity": {
+                "identifier": "user",
+                "key": "test_user",
+                "traits": {"purchased_products": ["pro", "addon"]}
+            },
+            "features": {},
+            "segments": {
+                "1": {
+                    "key": "1",
+                    "name": "pro_customers",
+                    "rules": [{
+                        "type": "ALL",
+                        "conditions": [
+                            {"operator": "CONTAINS_ALL", "property": "$.identity.traits.purchased_products", "value": ["pro"]},
+                            {"operator": "IN", "property": "$.identity.identifier", "value": ["user"]}
+                        ]
+                    }]
+                }
+            }
+        }))
+        .unwrap();
+
+        assert!(is_context_in_segment(&context, &context.segments["1"]));
+    }
+
+    #[rstest]
+    #[case("^beta", "BETA-tester", false)]
+    #[case("(?i)^beta", "BETA-tester", true)]
+    #[case("(?i:beta)-TESTER", "BETA-TESTER", true)]
+    #[case("(?i:beta)-TESTER", "BETA-tester", false)]
+    #[case("^admin$", "user\nadmin", false)]
+    #[case("(?m)^admin$", "user\nadmin", true)]
+    #[case("user.admin", "user\nadmin", false)]
+    #[case("(?s)user.admin", "user\nadmin", true)]
+    #[case("(?x) ^ user \\n admin $ ", "user\nadmin", true)]
+    #[case("(?i", "beta", false)]
+    #[case("(a{1000}){1000}", "a", false)]
+    fn test_regex_conditions(#[case] pattern: &str, #[case] value: &str, #[case] expected: bool) {
+        let context = path_context();
+        let mut identity = context.identity.clone().unwrap();
+        identity
+            .traits
+            .insert("tag".to_string(), FlagsmithValue::string(value));
+        let ec = ContextView::with_identity(&context, Some(&identity));
+        let condition = Condition {
+            operator: ConditionOperator::Regex,
+            property: "tag".to_string(),
+            value: ConditionValue::Single(pattern.to_string()),
+        };
+        let operands = ConditionOperands {
+            condition: &condition,
+            compiled: None,
+        };
+
+        let evaluation = context_matches_condition(&ec, &operands, "segment");
+
+        assert_eq!(evaluation.matched, expected);
+        assert_eq!(evaluation.strategy, ComparisonStrategy::Regex);
+    }
+
+    #[test]
+    fn test_build_regex_enforces_size_limit() {
+        let error = build_regex("(a{1000}){1000}").unwrap_err();
+
+        assert_eq!(error, regex::Error::CompiledTooBig(REGEX_SIZE_LIMIT));
+        assert!(build_regex("(a{10}){10}").is_ok());
+    }
 }
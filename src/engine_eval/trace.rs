@@ -0,0 +1,137 @@
+use super::context::{ConditionOperator, ConditionValue, SegmentRuleType};
+use crate::types::FlagsmithValue;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Explains how an evaluation result was reached.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct EvaluationTrace {
+    /// Every segment checked during evaluation, in evaluation order.
+    pub segments: Vec<SegmentTrace>,
+
+    /// Traces of features overridden by matched segments, indexed by feature name.
+    pub flags: HashMap<String, FlagTrace>,
+}
+
+/// Explains whether the context matched a segment.
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentTrace {
+    /// The segment key.
+    pub key: String,
+
+    /// The segment name.
+    pub name: String,
+
+    /// Whether the context is in the segment.
+    pub matched: bool,
+
+    /// Top-level rules visited, in evaluation order.
+    pub rules: Vec<RuleTrace>,
+}
+
+/// Explains whether the context matched a segment rule.
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleTrace {
+    /// The type of the rule (ALL, ANY, NONE).
+    pub rule_type: SegmentRuleType,
+
+    /// Whether the rule matched.
+    pub matched: bool,
+
+    /// Conditions visited, in evaluation order. Conditions skipped by short-circuiting are left out.
+    pub conditions: Vec<ConditionTrace>,
+
+    /// Nested rules visited, in evaluation order.
+    pub rules: Vec<RuleTrace>,
+}
+
+/// Explains whether the context matched a condition.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConditionTrace {
+    /// The property of the condition.
+    pub property: String,
+
+    /// The operator of the condition.
+    pub operator: ConditionOperator,
+
+    /// The value the condition compares against.
+    pub value: ConditionValue,
+
+    /// The value the property resolved to, if any.
+    pub context_value: Option<FlagsmithValue>,
+
+    /// How the context value was compared to the condition value.
+    pub strategy: ComparisonStrategy,
+
+    /// Whether the condition matched.
+    pub matched: bool,
+}
+
+/// The comparison used to evaluate a condition.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ComparisonStrategy {
+    /// Values compared as booleans.
+    Bool,
+    /// Values compared as integers.
+    Integer,
+    /// Values compared as floats.
+    Float,
+    /// Values compared as semantic versions.
+    Semver,
+    /// Values compared as loosely parsed versions.
+    Version,
+    /// Values compared as points in time.
+    Datetime,
+    /// Values compared as strings.
+    String,
+    /// Context value matched against a regular expression.
+    Regex,
+    /// Context value checked with a modulo operation.
+    Modulo,
+    /// Context value searched for a substring.
+    Substring,
+    /// Context value looked up in the condition value list.
+    Membership,
+    /// Context value looked up in the CIDR blocks of the condition value list.
+    Cidr,
+    /// Items of the context value compared to the condition value.
+    List,
+    /// Hashed percentage of the split key compared to the condition value.
+    PercentageSplit,
+    /// Only the presence of the context value was checked.
+    Presence,
+    /// Membership of the context in the referenced segment was checked.
+    SegmentMembership,
+    /// No comparison took place, e.g. because the property did not resolve.
+    Skipped,
+}
+
+/// Explains which feature context a flag result was built from.
+#[derive(Clone, Debug, Serialize)]
+pub struct FlagTrace {
+    /// The name of the feature.
+    pub feature_name: String,
+
+    /// Overrides from matched segments, in evaluation order.
+    pub overrides: Vec<OverrideTrace>,
+}
+
+/// A segment override competing for a feature.
+#[derive(Clone, Debug, Serialize)]
+pub struct OverrideTrace {
+    /// The key of the overriding segment.
+    pub segment_key: String,
+
+    /// The name of the overriding segment.
+    pub segment_name: String,
+
+    /// Priority of the override. Lower values indicate higher priority.
+    pub priority: Option<f64>,
+
+    /// Whether the schedule of this override is active. Inactive overrides are never applied.
+    pub active: bool,
+
+    /// Whether this override was applied.
+    pub selected: bool,
+}
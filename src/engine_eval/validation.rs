@@ -0,0 +1,691 @@
+use super::context::{
+    Condition, ConditionOperator, EngineEvaluationContext, FeatureContext, SegmentRule,
+};
+use super::segment_evaluator::{
+    build_regex, get_cyclic_segment_keys, parse_datetime_condition_value,
+    parse_semver_condition_value, parse_semver_list_condition_value, parse_version_condition_value,
+    CidrBlock,
+};
+use crate::engine::is_in_prerequisite_cycle;
+use crate::types::{FlagsmithValue, ValueConversionError};
+use semver::VersionReq;
+use std::collections::HashSet;
+use std::fmt;
+
+/// What is wrong with a part of an evaluation context.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationProblemKind {
+    /// A REGEX condition value is not a valid pattern.
+    InvalidRegex(String),
+    /// A REGEX condition value compiles to more bytes than the engine regex size limit.
+    RegexTooLarge(usize),
+    /// A `:semver` suffixed condition value is not a valid version, or list of versions.
+    InvalidSemver,
+    /// A SIZE_GREATER_THAN condition value is not a non-negative integer.
+    InvalidSize,
+    /// An IN_CIDR condition value is not a valid CIDR block.
+    InvalidCidr(String),
+    /// A `:version` suffixed condition value is not a valid loose version.
+    InvalidVersion,
+    /// A SEMVER_RANGE condition value is not a valid version requirement.
+    InvalidSemverRange(String),
+    /// A `:datetime` suffixed condition value is not a valid datetime.
+    InvalidDatetime,
+    /// A PERCENTAGE_SPLIT condition value is not a number between 0 and 100.
+    InvalidPercentage,
+    /// A MODULO condition value is not of the form `divisor|remainder`.
+    InvalidModulo,
+    /// A flag value does not parse as its declared type.
+    InvalidValue(ValueConversionError),
+    /// A priority is NaN.
+    NanPriority,
+    /// A variant weight is negative or NaN.
+    InvalidWeight,
+    /// The variant weights of a feature add up to more than 100.
+    WeightsOver100(f64),
+    /// `active_until` is not after `active_from`, so the feature context is never active.
+    EmptySchedule,
+    /// A segment overrides a feature the context does not define.
+    UnknownFeature(String),
+    /// More than one feature, or more than one override in a segment, has this name.
+    DuplicateName(String),
+    /// An IN_SEGMENT condition references a segment the context does not define.
+    UnknownSegment(String),
+    /// A segment references itself through IN_SEGMENT conditions.
+    SegmentCycle,
+    /// A feature depends on itself through its prerequisites.
+    PrerequisiteCycle,
+}
+
+impl fmt::Display for ValidationProblemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRegex(error) => write!(f, "invalid regex: {}", error),
+            Self::RegexTooLarge(limit) => {
+                write!(f, "regex exceeds the size limit of {} bytes", limit)
+            }
+            Self::InvalidSemver => write!(f, "invalid semver"),
+            Self::InvalidVersion => write!(f, "invalid version"),
+            Self::InvalidCidr(block) => write!(f, "invalid CIDR block {:?}", block),
+            Self::InvalidSize => write!(f, "invalid size"),
+            Self::InvalidSemverRange(error) => write!(f, "invalid semver range: {}", error),
+            Self::InvalidDatetime => write!(f, "invalid datetime"),
+            Self::InvalidPercentage => write!(f, "invalid percentage"),
+            Self::InvalidModulo => write!(f, "invalid modulo"),
+            Self::InvalidValue(error) => write!(f, "invalid value: {}", error),
+            Self::NanPriority => write!(f, "priority is NaN"),
+            Self::InvalidWeight => write!(f, "invalid weight"),
+            Self::WeightsOver100(total) => write!(f, "variant weights sum to {}", total),
+            Self::EmptySchedule => write!(f, "active_until is not after active_from"),
+            Self::UnknownFeature(name) => write!(f, "unknown feature {:?}", name),
+            Self::DuplicateName(name) => write!(f, "duplicate name {:?}", name),
+            Self::UnknownSegment(key) => write!(f, "unknown segment {:?}", key),
+            Self::SegmentCycle => write!(f, "segment references itself"),
+            Self::PrerequisiteCycle => write!(f, "feature depends on itself"),
+        }
+    }
+}
+
+/// A problem found in an evaluation context, with the path of the offending part.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationProblem {
+    /// Path to the offending part, e.g. `segments["12"].rules[0].conditions[2]`.
+    pub path: String,
+    /// What is wrong with it.
+    pub kind: ValidationProblemKind,
+}
+
+impl fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+/// Error returned by [`EngineEvaluationContext::validate`], listing every problem found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// The problems found, features first, then segments, each ordered by key.
+    pub problems: Vec<ValidationProblem>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid evaluation context")?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl EngineEvaluationContext {
+    /// Checks the context for problems that evaluation would otherwise silently ignore.
+    ///
+    /// Invalid condition values make their conditions evaluate to false, and NaN priorities
+    /// are treated as missing, so a context that fails validation can still be evaluated.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut problems = Vec::new();
+
+        let mut feature_keys: Vec<&String> = self.features.keys().collect();
+        feature_keys.sort();
+        let mut feature_names = HashSet::new();
+        for key in feature_keys {
+            let fc = &self.features[key];
+            let path = format!("features[{:?}]", key);
+            if !feature_names.insert(fc.name.as_str()) {
+                problems.push(problem(
+                    &path,
+                    ValidationProblemKind::DuplicateName(fc.name.clone()),
+                ));
+            }
+            validate_feature(&path, fc, &mut problems);
+            validate_prerequisites(self, &path, fc, &mut problems);
+        }
+
+        let cyclic_segment_keys = get_cyclic_segment_keys(self);
+        let mut segment_keys: Vec<&String> = self.segments.keys().collect();
+        segment_keys.sort();
+        for key in segment_keys {
+            let sc = &self.segments[key];
+            let path = format!("segments[{:?}]", key);
+            if cyclic_segment_keys.contains(key.as_str()) {
+                problems.push(problem(&path, ValidationProblemKind::SegmentCycle));
+            }
+            for (index, rule) in sc.rules.iter().enumerate() {
+                validate_rule(
+                    self,
+                    &format!("{}.rules[{}]", path, index),
+                    rule,
+                    &mut problems,
+                );
+            }
+
+            let mut override_names = HashSet::new();
+            for (index, fc) in sc.overrides.iter().enumerate() {
+                let path = format!("{}.overrides[{}]", path, index);
+                if !self.features.values().any(|f| f.name == fc.name) {
+                    problems.push(problem(
+                        &path,
+                        ValidationProblemKind::UnknownFeature(fc.name.clone()),
+                    ));
+                }
+                if !override_names.insert(fc.name.as_str()) {
+                    problems.push(problem(
+                        &path,
+                        ValidationProblemKind::DuplicateName(fc.name.clone()),
+                    ));
+                }
+                validate_feature(&path, fc, &mut problems);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { problems })
+        }
+    }
+}
+
+fn problem(path: &str, kind: ValidationProblemKind) -> ValidationProblem {
+    ValidationProblem {
+        path: path.to_string(),
+        kind,
+    }
+}
+
+/// Validates the value, priority, schedule and variants of a feature or override
+fn validate_feature(path: &str, fc: &FeatureContext, problems: &mut Vec<ValidationProblem>) {
+    validate_value(&format!("{}.value", path), &fc.value, problems);
+    validate_priority(&format!("{}.priority", path), fc.priority, problems);
+    if let (Some(active_from), Some(active_until)) = (fc.active_from, fc.active_until) {
+        if active_until <= active_from {
+            problems.push(problem(
+                &format!("{}.active_until", path),
+                ValidationProblemKind::EmptySchedule,
+            ));
+        }
+    }
+
+    let mut total_weight = 0.0;
+    for (index, variant) in fc.variants.iter().enumerate() {
+        let path = format!("{}.variants[{}]", path, index);
+        validate_value(&format!("{}.value", path), &variant.value, problems);
+        validate_priority(&format!("{}.priority", path), variant.priority, problems);
+        if variant.weight.is_nan() || variant.weight < 0.0 {
+            problems.push(problem(
+                &format!("{}.weight", path),
+                ValidationProblemKind::InvalidWeight,
+            ));
+        } else {
+            total_weight += variant.weight;
+        }
+    }
+    if total_weight > 100.0 {
+        problems.push(problem(
+            &format!("{}.variants", path),
+            ValidationProblemKind::WeightsOver100(total_weight),
+        ));
+    }
+}
+
+/// Validates the prerequisites of an environment feature
+fn validate_prerequisites(
+    ec: &EngineEvaluationContext,
+    path: &str,
+    fc: &FeatureContext,
+    problems: &mut Vec<ValidationProblem>,
+) {
+    if is_in_prerequisite_cycle(&ec.features, &fc.name) {
+        problems.push(problem(path, ValidationProblemKind::PrerequisiteCycle));
+    }
+    for (index, prerequisite) in fc.prerequisites.iter().enumerate() {
+        let path = format!("{}.prerequisites[{}]", path, index);
+        if !ec.features.contains_key(&prerequisite.feature) {
+            problems.push(problem(
+                &path,
+                ValidationProblemKind::UnknownFeature(prerequisite.feature.clone()),
+            ));
+        }
+        if let Some(value) = &prerequisite.value {
+            validate_value(&format!("{}.value", path), value, problems);
+        }
+    }
+}
+
+fn validate_value(path: &str, value: &FlagsmithValue, problems: &mut Vec<ValidationProblem>) {
+    if let Err(error) = value.validate() {
+        problems.push(problem(path, ValidationProblemKind::InvalidValue(error)));
+    }
+}
+
+fn validate_priority(path: &str, priority: Option<f64>, problems: &mut Vec<ValidationProblem>) {
+    if priority.is_some_and(f64::is_nan) {
+        problems.push(problem(path, ValidationProblemKind::NanPriority));
+    }
+}
+
+/// Validates the conditions of a rule and its nested rules
+fn validate_rule(
+    ec: &EngineEvaluationContext,
+    path: &str,
+    rule: &SegmentRule,
+    problems: &mut Vec<ValidationProblem>,
+) {
+    for (index, condition) in rule.conditions.iter().enumerate() {
+        if let Some(kind) = validate_condition(ec, condition) {
+            problems.push(problem(&format!("{}.conditions[{}]", path, index), kind));
+        }
+    }
+    for (index, nested) in rule.rules.iter().enumerate() {
+        validate_rule(ec, &format!("{}.rules[{}]", path, index), nested, problems);
+    }
+}
+
+/// Checks that the condition value is usable by the condition operator
+fn validate_condition(
+    ec: &EngineEvaluationContext,
+    condition: &Condition,
+) -> Option<ValidationProblemKind> {
+    let value = condition.value.as_string();
+    match condition.operator {
+        ConditionOperator::InSegment if !ec.segments.contains_key(&value) => {
+            Some(ValidationProblemKind::UnknownSegment(value))
+        }
+        ConditionOperator::Regex => match build_regex(&value) {
+            Ok(_) => None,
+            Err(regex::Error::CompiledTooBig(limit)) => {
+                Some(ValidationProblemKind::RegexTooLarge(limit))
+            }
+            Err(error) => Some(ValidationProblemKind::InvalidRegex(error.to_string())),
+        },
+        ConditionOperator::PercentageSplit => match value.parse::<f64>() {
+            Ok(percentage) if (0.0..=100.0).contains(&percentage) => None,
+            _ => Some(ValidationProblemKind::InvalidPercentage),
+        },
+        ConditionOperator::Modulo => {
+            let parts: Vec<Option<f64>> = value.split('|').map(|v| v.parse().ok()).collect();
+            match parts.as_slice() {
+                [Some(divisor), Some(_)] if *divisor != 0.0 => None,
+                _ => Some(ValidationProblemKind::InvalidModulo),
+            }
+        }
+        ConditionOperator::SemverRange => {
+            let range_str = value.strip_suffix(":semver").unwrap_or(&value);
+            VersionReq::parse(range_str)
+                .err()
+                .map(|error| ValidationProblemKind::InvalidSemverRange(error.to_string()))
+        }
+        ConditionOperator::SizeGreaterThan => value
+            .trim()
+            .parse::<usize>()
+            .is_err()
+            .then_some(ValidationProblemKind::InvalidSize),
+        ConditionOperator::InCidr => condition
+            .value
+            .as_vec()
+            .into_iter()
+            .find(|block| CidrBlock::parse(block).is_none())
+            .map(ValidationProblemKind::InvalidCidr),
+        ConditionOperator::In | ConditionOperator::NotIn | ConditionOperator::InIgnoreCase
+            if value.ends_with(":semver")
+                && parse_semver_list_condition_value(&condition.value).is_none() =>
+        {
+            Some(ValidationProblemKind::InvalidSemver)
+        }
+        ConditionOperator::In
+        | ConditionOperator::NotIn
+        | ConditionOperator::InIgnoreCase
+        | ConditionOperator::ContainsAny
+        | ConditionOperator::ContainsAll
+        | ConditionOperator::InSegment
+        | ConditionOperator::IsSet
+        | ConditionOperator::IsNotSet => None,
+        _ if value.ends_with(":datetime") && parse_datetime_condition_value(&value).is_none() => {
+            Some(ValidationProblemKind::InvalidDatetime)
+        }
+        _ if value.ends_with(":semver") && parse_semver_condition_value(&value).is_none() => {
+            Some(ValidationProblemKind::InvalidSemver)
+        }
+        _ if value.ends_with(":version") && parse_version_condition_value(&value).is_none() => {
+            Some(ValidationProblemKind::InvalidVersion)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn context(segments: serde_json::Value) -> EngineEvaluationContext {
+        serde_json::from_value(serde_json::json!({
+            "environment": {"key": "test", "name": "test"},
+            "features": {
+                "feature": {"key": "1", "name": "feature", "enabled": true, "value": "default"}
+            },
+            "segments": segments
+        }))
+        .unwrap()
+    }
+
+    fn condition_context(operator: &str, value: &str) -> EngineEvaluationContext {
+        context(serde_json::json!({
+            "12": {
+                "key": "12",
+                "name": "segment",
+                "rules": [{
+                    "type": "ALL",
+                    "conditions": [],
+                    "rules": [{
+                        "type": "ANY",
+                        "conditions": [
+                            {"operator": "IS_SET", "property": "a", "value": ""},
+                            {"operator": operator, "property": "a", "value": value}
+                        ]
+                    }]
+                }]
+            }
+        }))
+    }
+
+    #[rstest]
+    #[case("REGEX", "[a-z")]
+    #[case("REGEX", "(?q)beta")]
+    #[case("EQUAL", "1.0:semver")]
+    #[case("GREATER_THAN", "not.a.version:semver")]
+    #[case("LESS_THAN", "yesterday:datetime")]
+    #[case("LESS_THAN", "now-30:datetime")]
+    #[case("PERCENTAGE_SPLIT", "abc")]
+    #[case("PERCENTAGE_SPLIT", "101")]
+    #[case("MODULO", "2")]
+    #[case("MODULO", "0|1")]
+    #[case("SEMVER_RANGE", "^2.x.y")]
+    #[case("IN", "1.2.0,latest:semver")]
+    #[case("NOT_IN", "1.2.0,1.3:semver")]
+    #[case("GREATER_THAN", "4..12:version")]
+    #[case("IN_CIDR", "10.0.0.0/8,10.0.0.0/33")]
+    #[case("IN_CIDR", "office")]
+    #[case("SIZE_GREATER_THAN", "-1")]
+    #[case("SIZE_GREATER_THAN", "two")]
+    fn test_validate_reports_invalid_condition(#[case] operator: &str, #[case] value: &str) {
+        let error = condition_context(operator, value).validate().unwrap_err();
+
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(
+            error.problems[0].path,
+            r#"segments["12"].rules[0].rules[0].conditions[1]"#
+        );
+    }
+
+    #[rstest]
+    #[case("REGEX", "^[a-z]+$")]
+    #[case("REGEX", "(?im)^beta$")]
+    #[case("EQUAL", "1.0.0:semver")]
+    #[case("LESS_THAN", "2024-01-01T00:00:00Z:datetime")]
+    #[case("LESS_THAN", "now-30d:datetime")]
+    #[case("PERCENTAGE_SPLIT", "50.5")]
+    #[case("MODULO", "2|0")]
+    #[case("IN", "a,b,c")]
+    #[case("IN", "1.2.0,1.3.0+build:semver")]
+    #[case("SEMVER_RANGE", ">=1.2, <2")]
+    #[case("SEMVER_RANGE", "~2.3:semver")]
+    #[case("GREATER_THAN", "2024.10.1-rc.1:version")]
+    #[case("IN_CIDR", "10.0.0.0/8, 2001:db8::/32,192.168.1.1")]
+    #[case("SIZE_GREATER_THAN", "2")]
+    #[case("CONTAINS_ALL", "admin,editor")]
+    fn test_validate_accepts_valid_condition(#[case] operator: &str, #[case] value: &str) {
+        assert!(condition_context(operator, value).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_invalid_regex_display() {
+        let error = condition_context("REGEX", "[a-z").validate().unwrap_err();
+
+        assert!(matches!(
+            error.problems[0].kind,
+            ValidationProblemKind::InvalidRegex(_)
+        ));
+        assert!(error.problems[0]
+            .to_string()
+            .starts_with(r#"segments["12"].rules[0].rules[0].conditions[1]: invalid regex"#));
+    }
+
+    #[test]
+    fn test_validate_reports_regex_over_size_limit() {
+        let error = condition_context("REGEX", "(a{1000}){1000}")
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![ValidationProblem {
+                path: r#"segments["12"].rules[0].rules[0].conditions[1]"#.to_string(),
+                kind: ValidationProblemKind::RegexTooLarge(
+                    crate::engine_eval::segment_evaluator::REGEX_SIZE_LIMIT
+                ),
+            }]
+        );
+        assert_eq!(
+            error.problems[0].to_string(),
+            format!(
+                r#"segments["12"].rules[0].rules[0].conditions[1]: regex exceeds the size limit of {} bytes"#,
+                10 * (1 << 20)
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_feature_problems() {
+        let mut ec = context(serde_json::json!({}));
+        let mut feature = ec.features["feature"].clone();
+        feature.name = "other".to_string();
+        feature.priority = Some(f64::NAN);
+        feature.variants = serde_json::from_value(serde_json::json!([
+            {"value": "a", "weight": 60.0},
+            {"value": "b", "weight": 50.0, "priority": 1.0},
+            {"value": "c", "weight": -1.0}
+        ]))
+        .unwrap();
+        feature.variants[1].priority = Some(f64::NAN);
+        ec.features.insert("other".to_string(), feature);
+        ec.features.get_mut("feature").unwrap().value = FlagsmithValue {
+            value: "yes".to_string(),
+            value_type: crate::types::FlagsmithValueType::Bool,
+        };
+
+        let error = ec.validate().unwrap_err();
+        let problems: Vec<(String, ValidationProblemKind)> = error
+            .problems
+            .into_iter()
+            .map(|p| (p.path, p.kind))
+            .collect();
+
+        assert_eq!(problems.len(), 5);
+        assert_eq!(problems[0].0, r#"features["feature"].value"#);
+        assert!(matches!(
+            problems[0].1,
+            ValidationProblemKind::InvalidValue(_)
+        ));
+        assert_eq!(
+            problems[1..],
+            [
+                (
+                    r#"features["other"].priority"#.to_string(),
+                    ValidationProblemKind::NanPriority
+                ),
+                (
+                    r#"features["other"].variants[1].priority"#.to_string(),
+                    ValidationProblemKind::NanPriority
+                ),
+                (
+                    r#"features["other"].variants[2].weight"#.to_string(),
+                    ValidationProblemKind::InvalidWeight
+                ),
+                (
+                    r#"features["other"].variants"#.to_string(),
+                    ValidationProblemKind::WeightsOver100(110.0)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_empty_schedules() {
+        let mut ec = context(serde_json::json!({}));
+        let feature = ec.features.get_mut("feature").unwrap();
+        feature.active_from = Some("2024-02-01T00:00:00Z".parse().unwrap());
+        feature.active_until = Some("2024-01-01T00:00:00Z".parse().unwrap());
+
+        let error = ec.validate().unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![ValidationProblem {
+                path: r#"features["feature"].active_until"#.to_string(),
+                kind: ValidationProblemKind::EmptySchedule,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_duplicate_feature_names() {
+        let mut ec = context(serde_json::json!({}));
+        let feature = ec.features["feature"].clone();
+        ec.features.insert("alias".to_string(), feature);
+
+        let error = ec.validate().unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![ValidationProblem {
+                path: r#"features["feature"]"#.to_string(),
+                kind: ValidationProblemKind::DuplicateName("feature".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_override_problems() {
+        let ec = context(serde_json::json!({
+            "1": {
+                "key": "1",
+                "name": "segment",
+                "rules": [],
+                "overrides": [
+                    {"key": "1", "name": "feature", "enabled": true, "value": "a"},
+                    {"key": "1", "name": "feature", "enabled": true, "value": "b"},
+                    {"key": "2", "name": "missing", "enabled": true, "value": "c"}
+                ]
+            }
+        }));
+
+        let error = ec.validate().unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![
+                ValidationProblem {
+                    path: r#"segments["1"].overrides[1]"#.to_string(),
+                    kind: ValidationProblemKind::DuplicateName("feature".to_string()),
+                },
+                ValidationProblem {
+                    path: r#"segments["1"].overrides[2]"#.to_string(),
+                    kind: ValidationProblemKind::UnknownFeature("missing".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid evaluation context\n  segments[\"1\"].overrides[1]: duplicate name \"feature\"\n  segments[\"1\"].overrides[2]: unknown feature \"missing\""
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_segment_references() {
+        let in_segment = |key: &str, referenced_key: &str| {
+            serde_json::json!({
+                "key": key,
+                "name": "segment",
+                "rules": [{
+                    "type": "ALL",
+                    "conditions": [{"operator": "IN_SEGMENT", "property": "", "value": referenced_key}]
+                }]
+            })
+        };
+        let ec = context(serde_json::json!({
+            "a": in_segment("a", "b"),
+            "b": in_segment("b", "a"),
+            "c": in_segment("c", "a"),
+            "d": in_segment("d", "missing")
+        }));
+
+        let error = ec.validate().unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![
+                ValidationProblem {
+                    path: r#"segments["a"]"#.to_string(),
+                    kind: ValidationProblemKind::SegmentCycle,
+                },
+                ValidationProblem {
+                    path: r#"segments["b"]"#.to_string(),
+                    kind: ValidationProblemKind::SegmentCycle,
+                },
+                ValidationProblem {
+                    path: r#"segments["d"].rules[0].conditions[0]"#.to_string(),
+                    kind: ValidationProblemKind::UnknownSegment("missing".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_prerequisite_problems() {
+        let mut ec = context(serde_json::json!({}));
+        let feature = |name: &str, prerequisites: serde_json::Value| {
+            serde_json::from_value::<FeatureContext>(serde_json::json!({
+                "key": name,
+                "name": name,
+                "enabled": true,
+                "value": "",
+                "prerequisites": prerequisites
+            }))
+            .unwrap()
+        };
+        for fc in [
+            feature("a", serde_json::json!([{"feature": "b"}])),
+            feature("b", serde_json::json!([{"feature": "a"}])),
+            feature(
+                "c",
+                serde_json::json!([{"feature": "a"}, {"feature": "missing"}]),
+            ),
+        ] {
+            ec.features.insert(fc.name.clone(), fc);
+        }
+
+        let error = ec.validate().unwrap_err();
+
+        assert_eq!(
+            error.problems,
+            vec![
+                ValidationProblem {
+                    path: r#"features["a"]"#.to_string(),
+                    kind: ValidationProblemKind::PrerequisiteCycle,
+                },
+                ValidationProblem {
+                    path: r#"features["b"]"#.to_string(),
+                    kind: ValidationProblemKind::PrerequisiteCycle,
+                },
+                ValidationProblem {
+                    path: r#"features["c"].prerequisites[1]"#.to_string(),
+                    kind: ValidationProblemKind::UnknownFeature("missing".to_string()),
+                },
+            ]
+        );
+    }
+}
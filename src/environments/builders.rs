@@ -1,9 +1,16 @@
 use super::Environment;
 use super::EnvironmentAPIKey;
+use crate::error::Error;
 
+/// Builds an environment from its JSON document, panicking if the document is invalid
 pub fn build_environment_struct(value: serde_json::Value) -> Environment {
-    let environment: Environment = serde_json::from_value(value).unwrap();
-    return environment;
+    try_build_environment_struct(value).unwrap()
+}
+
+/// Builds an environment from its JSON document
+pub fn try_build_environment_struct(value: serde_json::Value) -> Result<Environment, Error> {
+    let environment: Environment = serde_json::from_value(value)?;
+    Ok(environment)
 }
 
 pub fn build_environment_api_key_struct(value: serde_json::Value) -> EnvironmentAPIKey {
@@ -32,4 +39,14 @@ mod tests {
         // Then
         assert_eq!(api_key_struct.key, key);
     }
+
+    #[test]
+    fn try_build_environment_struct_returns_error_for_invalid_document() {
+        // Given
+        let environment_json = serde_json::json!({"api_key": "test_key"});
+        // When
+        let error = try_build_environment_struct(environment_json).unwrap_err();
+        // Then
+        assert_eq!(error.kind, crate::error::ErrorKind::InvalidDocument);
+    }
 }
@@ -1,28 +1,170 @@
+use crate::engine_eval::validation::ValidationError;
+use crate::types::ValueConversionError;
+use std::error;
 use std::fmt;
 
 /// Wraps several types of errors.
 #[derive(Debug)]
 pub struct Error {
     pub kind: ErrorKind,
+    message: Option<String>,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
 }
 
 /// Defines error kind.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorKind {
     FeatureStateNotFound,
     DuplicateFeatureState,
+    /// A JSON document could not be parsed into the requested type.
+    InvalidDocument,
+    /// A datetime string is not in the rfc3339 format, as returned by `utils::datetime::parse`.
+    /// Invalid datetimes in JSON documents are reported by builders as `InvalidDocument`.
+    InvalidDatetime,
+    /// A flag value could not be converted to the requested type.
+    ValueConversion,
+    /// An evaluation context failed validation.
+    InvalidContext,
+    /// A document could not be mapped to an evaluation context.
+    Mapping,
 }
 
 impl Error {
     pub fn new(kind: ErrorKind) -> Error {
-        Error { kind }
+        Error {
+            kind,
+            message: None,
+            source: None,
+        }
+    }
+
+    /// Creates an error of the given kind, explained by a message
+    pub fn with_message(kind: ErrorKind, message: impl Into<String>) -> Error {
+        Error {
+            kind,
+            message: Some(message.into()),
+            source: None,
+        }
+    }
+
+    /// Creates an error of the given kind, caused by another error
+    pub fn with_source<E>(kind: ErrorKind, source: E) -> Error
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        Error {
+            kind,
+            message: None,
+            source: Some(source.into()),
+        }
     }
 }
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind {
-            ErrorKind::FeatureStateNotFound => write!(f, "Feature State Not Found"),
-            ErrorKind::DuplicateFeatureState => write!(f, "Feature State already exists"),
+            ErrorKind::FeatureStateNotFound => write!(f, "Feature State Not Found")?,
+            ErrorKind::DuplicateFeatureState => write!(f, "Feature State already exists")?,
+            ErrorKind::InvalidDocument => write!(f, "Invalid document")?,
+            ErrorKind::InvalidDatetime => write!(f, "Invalid datetime")?,
+            ErrorKind::ValueConversion => write!(f, "Value conversion failed")?,
+            ErrorKind::InvalidContext => write!(f, "Invalid evaluation context")?,
+            ErrorKind::Mapping => write!(f, "Mapping to evaluation context failed")?,
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
         }
+        match &self.source {
+            Some(source) => write!(f, ": {}", source),
+            None => Ok(()),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::with_source(ErrorKind::InvalidDocument, error)
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(error: chrono::ParseError) -> Self {
+        Error::with_source(ErrorKind::InvalidDatetime, error)
+    }
+}
+
+impl From<ValueConversionError> for Error {
+    fn from(error: ValueConversionError) -> Self {
+        Error::with_source(ErrorKind::ValueConversion, error)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        Error::with_source(ErrorKind::InvalidContext, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FlagsmithValue;
+    use std::error::Error as _;
+
+    #[test]
+    fn error_without_source_displays_kind() {
+        let error = Error::new(ErrorKind::FeatureStateNotFound);
+
+        assert_eq!(error.to_string(), "Feature State Not Found");
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn error_with_message_displays_message_without_source() {
+        let error = Error::with_message(ErrorKind::Mapping, "segment 1: unknown operator");
+
+        assert_eq!(
+            error.to_string(),
+            "Mapping to evaluation context failed: segment 1: unknown operator"
+        );
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn error_from_value_conversion_keeps_source() {
+        let conversion_error = FlagsmithValue::string("abc").as_i64().unwrap_err();
+
+        let error = Error::from(conversion_error.clone());
+
+        assert_eq!(error.kind, ErrorKind::ValueConversion);
+        assert_eq!(
+            error.to_string(),
+            format!("Value conversion failed: {}", conversion_error)
+        );
+        assert_eq!(
+            error
+                .source()
+                .unwrap()
+                .downcast_ref::<ValueConversionError>(),
+            Some(&conversion_error)
+        );
+    }
+
+    #[test]
+    fn error_from_serde_json_is_invalid_document() {
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+
+        let error = Error::from(json_error);
+
+        assert_eq!(error.kind, ErrorKind::InvalidDocument);
+        assert!(error.source().unwrap().is::<serde_json::Error>());
     }
 }
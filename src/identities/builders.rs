@@ -1,6 +1,13 @@
 use super::Identity;
+use crate::error::Error;
 
+/// Builds an identity from its JSON document, panicking if the document is invalid
 pub fn build_identity_struct(value: serde_json::Value) -> Identity {
-    let identity: Identity = serde_json::from_value(value).unwrap();
-    return identity;
+    try_build_identity_struct(value).unwrap()
+}
+
+/// Builds an identity from its JSON document
+pub fn try_build_identity_struct(value: serde_json::Value) -> Result<Identity, Error> {
+    let identity: Identity = serde_json::from_value(value)?;
+    Ok(identity)
 }
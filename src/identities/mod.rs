@@ -67,4 +67,22 @@ mod tests {
         let identity: Identity = serde_json::from_str(identity_json).unwrap();
         assert_eq!(identity.composite_key(), expected_composite_key)
     }
+
+    #[test]
+    fn try_build_identity_struct_returns_error_for_invalid_datetime() {
+        let identity_json = serde_json::json!({
+            "identifier": "test_user",
+            "environment_api_key": "test_api_key",
+            "created_date": "yesterday",
+            "identity_features": [],
+            "identity_traits": [],
+            "identity_uuid": ""
+        });
+
+        let error = builders::try_build_identity_struct(identity_json).unwrap_err();
+
+        // Datetimes are parsed while deserializing, so the whole document is invalid
+        assert_eq!(error.kind, crate::error::ErrorKind::InvalidDocument);
+        assert!(error.to_string().contains("Invalid datetime"));
+    }
 }
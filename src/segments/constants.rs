@@ -17,4 +17,16 @@ pub const PERCENTAGE_SPLIT: &str = "PERCENTAGE_SPLIT";
 pub const MODULO: &str = "MODULO";
 pub const IS_SET: &str = "IS_SET";
 pub const IS_NOT_SET: &str = "IS_NOT_SET";
+pub const IN_SEGMENT: &str = "IN_SEGMENT";
 pub const IN: &str = "IN";
+pub const NOT_IN: &str = "NOT_IN";
+pub const STARTS_WITH: &str = "STARTS_WITH";
+pub const ENDS_WITH: &str = "ENDS_WITH";
+pub const EQUAL_IGNORE_CASE: &str = "EQUAL_IGNORE_CASE";
+pub const CONTAINS_IGNORE_CASE: &str = "CONTAINS_IGNORE_CASE";
+pub const IN_IGNORE_CASE: &str = "IN_IGNORE_CASE";
+pub const SEMVER_RANGE: &str = "SEMVER_RANGE";
+pub const IN_CIDR: &str = "IN_CIDR";
+pub const CONTAINS_ANY: &str = "CONTAINS_ANY";
+pub const CONTAINS_ALL: &str = "CONTAINS_ALL";
+pub const SIZE_GREATER_THAN: &str = "SIZE_GREATER_THAN";
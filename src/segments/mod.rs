@@ -1,8 +1,12 @@
+use super::engine_eval::context::ConditionValue;
+use super::engine_eval::segment_evaluator::{
+    build_regex, get_list_items, parse_cidr_condition_value,
+};
 use super::features;
 use super::types::{FlagsmithValue, FlagsmithValueType};
-use regex::Regex;
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 pub mod constants;
 pub mod evaluator;
 
@@ -19,7 +23,20 @@ impl SegmentCondition {
         if self.operator.as_str() == constants::MODULO {
             return self.modulo_operations(trait_value, &self.value.as_ref().unwrap());
         }
-        if self.operator.as_str() == constants::IN {
+        if let constants::CONTAINS_ANY | constants::CONTAINS_ALL | constants::SIZE_GREATER_THAN =
+            self.operator.as_str()
+        {
+            return self.list_operations(trait_value, self.value.as_ref().unwrap());
+        }
+        if self.operator.as_str() == constants::IN_CIDR {
+            return self.in_cidr_operations(&trait_value.value, self.value.as_ref().unwrap());
+        }
+        if self.operator.as_str() == constants::SEMVER_RANGE {
+            return self.semver_range_operations(&trait_value.value, self.value.as_ref().unwrap());
+        }
+        if let constants::IN | constants::NOT_IN | constants::IN_IGNORE_CASE =
+            self.operator.as_str()
+        {
             return match trait_value.value_type {
                 FlagsmithValueType::String => {
                     self.in_operations(&trait_value.value, &self.value.as_ref().unwrap())
@@ -28,7 +45,7 @@ impl SegmentCondition {
                     let trait_value: String = trait_value.value.to_string();
                     self.in_operations(&trait_value, &self.value.as_ref().unwrap())
                 }
-                _ => false,
+                _ => self.operator.as_str() == constants::NOT_IN,
             };
         }
         return match trait_value.value_type {
@@ -68,10 +85,15 @@ impl SegmentCondition {
             constants::NOT_EQUAL => trait_value != segment_value,
             constants::CONTAINS => trait_value.contains(segment_value),
             constants::NOT_CONTAINS => !trait_value.contains(segment_value),
-            constants::REGEX => {
-                let re = Regex::new(segment_value).unwrap();
-                re.is_match(&trait_value)
+            constants::STARTS_WITH => trait_value.starts_with(segment_value),
+            constants::ENDS_WITH => trait_value.ends_with(segment_value),
+            constants::EQUAL_IGNORE_CASE => {
+                trait_value.to_lowercase() == segment_value.to_lowercase()
             }
+            constants::CONTAINS_IGNORE_CASE => trait_value
+                .to_lowercase()
+                .contains(&segment_value.to_lowercase()),
+            constants::REGEX => build_regex(segment_value).is_ok_and(|re| re.is_match(trait_value)),
             _ => false,
         }
     }
@@ -106,6 +128,47 @@ impl SegmentCondition {
             _ => false,
         }
     }
+    fn semver_range_operations(&self, trait_value: &str, segment_value: &str) -> bool {
+        let segment_value = segment_value
+            .strip_suffix(":semver")
+            .unwrap_or(segment_value);
+        match (
+            Version::parse(trait_value),
+            VersionReq::parse(segment_value),
+        ) {
+            (Ok(trait_value), Ok(segment_value)) => segment_value.matches(&trait_value),
+            _ => false,
+        }
+    }
+    fn list_operations(&self, trait_value: &FlagsmithValue, segment_value: &str) -> bool {
+        let items = match get_list_items(trait_value) {
+            Some(items) => items,
+            None => return false,
+        };
+        let segment_values = ConditionValue::Single(segment_value.to_string()).as_vec();
+        match self.operator.as_str() {
+            constants::CONTAINS_ANY => items
+                .iter()
+                .any(|item| segment_values.iter().any(|value| value == item)),
+            constants::CONTAINS_ALL => segment_values
+                .iter()
+                .all(|value| items.iter().any(|item| item == value)),
+            constants::SIZE_GREATER_THAN => segment_value
+                .trim()
+                .parse::<usize>()
+                .is_ok_and(|size| items.len() > size),
+            _ => false,
+        }
+    }
+    fn in_cidr_operations(&self, trait_value: &str, segment_value: &str) -> bool {
+        let address: IpAddr = match trait_value.trim().parse() {
+            Ok(address) => address,
+            Err(_) => return false,
+        };
+        let segment_value = ConditionValue::Single(segment_value.to_string());
+        parse_cidr_condition_value(&segment_value)
+            .is_some_and(|blocks| blocks.iter().any(|block| block.contains(address)))
+    }
     fn bool_operations(&self, trait_value: bool, segment_value: bool) -> bool {
         match self.operator.as_str() {
             constants::EQUAL => trait_value == segment_value,
@@ -129,7 +192,15 @@ impl SegmentCondition {
         }
     }
     fn in_operations(&self, trait_value: &str, segment_value: &str) -> bool {
-        segment_value.split(',').any(|x| x == trait_value)
+        let mut values = segment_value.split(',');
+        match self.operator.as_str() {
+            constants::NOT_IN => !values.any(|x| x == trait_value),
+            constants::IN_IGNORE_CASE => {
+                let trait_value = trait_value.to_lowercase();
+                values.any(|x| x.to_lowercase() == trait_value)
+            }
+            _ => values.any(|x| x == trait_value),
+        }
     }
 }
 
@@ -328,6 +399,21 @@ mod tests {
         true
     )]
     #[case(constants::REGEX, "foo", FlagsmithValueType::String, r"[a-z]+", true)]
+    #[case(
+        constants::REGEX,
+        "FOO",
+        FlagsmithValueType::String,
+        r"(?i)^foo$",
+        true
+    )]
+    #[case(constants::REGEX, "foo", FlagsmithValueType::String, r"[a-z", false)]
+    #[case(
+        constants::REGEX,
+        "a",
+        FlagsmithValueType::String,
+        r"(a{1000}){1000}",
+        false
+    )]
     #[case(constants::IN, "foo", FlagsmithValueType::String, "", false)]
     #[case(constants::IN, "foo", FlagsmithValueType::String, "foo,bar", true)]
     #[case(constants::IN, "bar", FlagsmithValueType::String, "foo,bar", true)]
@@ -340,6 +426,177 @@ mod tests {
     // due to ambiguous serialization across supported platforms.
     #[case(constants::IN, "1.5", FlagsmithValueType::Float, "1.5", false)]
     #[case(constants::IN, "false", FlagsmithValueType::Bool, "false", false)]
+    #[case(constants::NOT_IN, "foo", FlagsmithValueType::String, "foo,bar", false)]
+    #[case(constants::NOT_IN, "baz", FlagsmithValueType::String, "foo,bar", true)]
+    #[case(constants::NOT_IN, "5", FlagsmithValueType::Integer, "1,2,3,4", true)]
+    #[case(
+        constants::IN_IGNORE_CASE,
+        "FOO",
+        FlagsmithValueType::String,
+        "foo,bar",
+        true
+    )]
+    #[case(
+        constants::IN_IGNORE_CASE,
+        "baz",
+        FlagsmithValueType::String,
+        "foo,bar",
+        false
+    )]
+    #[case(
+        constants::STARTS_WITH,
+        "foobar",
+        FlagsmithValueType::String,
+        "foo",
+        true
+    )]
+    #[case(
+        constants::STARTS_WITH,
+        "foobar",
+        FlagsmithValueType::String,
+        "bar",
+        false
+    )]
+    #[case(
+        constants::ENDS_WITH,
+        "foobar",
+        FlagsmithValueType::String,
+        "bar",
+        true
+    )]
+    #[case(
+        constants::ENDS_WITH,
+        "foobar",
+        FlagsmithValueType::String,
+        "foo",
+        false
+    )]
+    #[case(
+        constants::EQUAL_IGNORE_CASE,
+        "FooBar",
+        FlagsmithValueType::String,
+        "foobar",
+        true
+    )]
+    #[case(
+        constants::EQUAL_IGNORE_CASE,
+        "FooBar",
+        FlagsmithValueType::String,
+        "foo",
+        false
+    )]
+    #[case(
+        constants::CONTAINS_IGNORE_CASE,
+        "FooBar",
+        FlagsmithValueType::String,
+        "OB",
+        true
+    )]
+    #[case(
+        constants::CONTAINS_IGNORE_CASE,
+        "FooBar",
+        FlagsmithValueType::String,
+        "baz",
+        false
+    )]
+    #[case(
+        constants::SEMVER_RANGE,
+        "2.3.1",
+        FlagsmithValueType::String,
+        "^2.3",
+        true
+    )]
+    #[case(
+        constants::SEMVER_RANGE,
+        "2.3.1",
+        FlagsmithValueType::String,
+        "^2.3:semver",
+        true
+    )]
+    #[case(
+        constants::SEMVER_RANGE,
+        "3.0.0",
+        FlagsmithValueType::String,
+        ">=1.2, <3",
+        false
+    )]
+    #[case(
+        constants::SEMVER_RANGE,
+        "3.0",
+        FlagsmithValueType::String,
+        "^3",
+        false
+    )]
+    #[case(
+        constants::IN_CIDR,
+        "10.1.2.3",
+        FlagsmithValueType::String,
+        "10.0.0.0/8, 2001:db8::/32",
+        true
+    )]
+    #[case(
+        constants::IN_CIDR,
+        "2001:db8::1",
+        FlagsmithValueType::String,
+        "10.0.0.0/8,2001:db8::/32",
+        true
+    )]
+    #[case(
+        constants::IN_CIDR,
+        "11.1.2.3",
+        FlagsmithValueType::String,
+        "10.0.0.0/8",
+        false
+    )]
+    #[case(
+        constants::IN_CIDR,
+        "10.1.2.3",
+        FlagsmithValueType::String,
+        "10.0.0.0/8,office",
+        false
+    )]
+    #[case(
+        constants::CONTAINS_ANY,
+        r#"["admin","editor"]"#,
+        FlagsmithValueType::Json,
+        "viewer,admin",
+        true
+    )]
+    #[case(
+        constants::CONTAINS_ANY,
+        "admin,editor",
+        FlagsmithValueType::String,
+        "admin",
+        false
+    )]
+    #[case(
+        constants::CONTAINS_ALL,
+        r#"["admin","editor"]"#,
+        FlagsmithValueType::Json,
+        "editor,admin",
+        true
+    )]
+    #[case(
+        constants::CONTAINS_ALL,
+        r#"["admin","editor"]"#,
+        FlagsmithValueType::Json,
+        "admin,viewer",
+        false
+    )]
+    #[case(
+        constants::SIZE_GREATER_THAN,
+        r#"["admin","editor"]"#,
+        FlagsmithValueType::Json,
+        "1",
+        true
+    )]
+    #[case(
+        constants::SIZE_GREATER_THAN,
+        r#"["admin","editor"]"#,
+        FlagsmithValueType::Json,
+        "2",
+        false
+    )]
     fn segemnt_condition_matches_trait_value(
         #[case] operator: &str,
         #[case] trait_value: &str,
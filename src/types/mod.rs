@@ -1,6 +1,9 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use serde::de;
+use num_bigint::BigInt;
+use num_traits::cast::ToPrimitive;
+use serde::{de, ser};
+use std::error;
 use std::fmt;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -10,6 +13,8 @@ pub enum FlagsmithValueType {
     Integer,
     Float,
     None,
+    /// A JSON object or array, stored as compact JSON text.
+    Json,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -33,21 +38,76 @@ impl Serialize for FlagsmithValue {
         S: Serializer,
     {
         match self.value_type {
-            FlagsmithValueType::Bool => serializer.serialize_bool(self.value.parse().unwrap()),
-            FlagsmithValueType::Integer => serializer.serialize_i64(self.value.parse().unwrap()),
+            FlagsmithValueType::Bool => {
+                serializer.serialize_bool(self.parse_bool_value().map_err(ser::Error::custom)?)
+            }
+            FlagsmithValueType::Integer => serialize_integer(self, serializer),
             FlagsmithValueType::String => serializer.serialize_str(self.value.as_str()),
             FlagsmithValueType::None => serializer.serialize_none(),
-            FlagsmithValueType::Float => serializer.serialize_f64(self.value.parse().unwrap()),
+            FlagsmithValueType::Float => {
+                serializer.serialize_f64(self.parse_float_value().map_err(ser::Error::custom)?)
+            }
+            FlagsmithValueType::Json => self
+                .parse_json_value()
+                .map_err(ser::Error::custom)?
+                .serialize(serializer),
         }
     }
 }
 
+/// Serializes an Integer value as the smallest fitting integer type
+fn serialize_integer<S>(value: &FlagsmithValue, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let integer = value.parse_integer_value().map_err(ser::Error::custom)?;
+    if let Some(v) = integer.to_i64() {
+        return serializer.serialize_i64(v);
+    }
+    if let Some(v) = integer.to_u64() {
+        return serializer.serialize_u64(v);
+    }
+    if let Some(v) = integer.to_i128() {
+        return serializer.serialize_i128(v);
+    }
+    if let Some(v) = integer.to_u128() {
+        return serializer.serialize_u128(v);
+    }
+    serialize_big_integer(value, serializer)
+}
+
+/// Serializes an integer beyond 128 bits as an arbitrary precision JSON number
+#[cfg(feature = "arbitrary_precision")]
+fn serialize_big_integer<S>(value: &FlagsmithValue, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value
+        .value
+        .parse::<serde_json::Number>()
+        .map_err(ser::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Fails to serialize an integer beyond 128 bits, which needs the `arbitrary_precision` feature
+#[cfg(not(feature = "arbitrary_precision"))]
+fn serialize_big_integer<S>(value: &FlagsmithValue, _serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    Err(ser::Error::custom(format!(
+        "integer {} is out of range, enable the arbitrary_precision feature to serialize it",
+        value.value
+    )))
+}
+
 struct FlagsmithValueVisitor;
 impl<'de> de::Visitor<'de> for FlagsmithValueVisitor {
     type Value = FlagsmithValue;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("an integer, a string, a float, a None or boolean")
+        formatter
+            .write_str("an integer, a string, a float, a None, a boolean, an object or an array")
     }
 
     fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
@@ -78,6 +138,24 @@ impl<'de> de::Visitor<'de> for FlagsmithValueVisitor {
             value_type: FlagsmithValueType::Integer,
         })
     }
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(FlagsmithValue {
+            value: v.to_string(),
+            value_type: FlagsmithValueType::Integer,
+        })
+    }
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(FlagsmithValue {
+            value: v.to_string(),
+            value_type: FlagsmithValueType::Integer,
+        })
+    }
     fn visit_unit<E>(self) -> Result<Self::Value, E>
     where
         E: de::Error,
@@ -105,6 +183,24 @@ impl<'de> de::Visitor<'de> for FlagsmithValueVisitor {
             value_type: FlagsmithValueType::Bool,
         })
     }
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        // Arbitrary precision JSON numbers are also deserialized as maps
+        let value = serde_json::Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        match value {
+            serde_json::Value::Number(number) => Ok(FlagsmithValue::number(&number)),
+            _ => Ok(FlagsmithValue::json(&value)),
+        }
+    }
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let value = serde_json::Value::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+        Ok(FlagsmithValue::json(&value))
+    }
 }
 impl<'de> Deserialize<'de> for FlagsmithValue {
     fn deserialize<D>(deserializer: D) -> Result<FlagsmithValue, D::Error>
@@ -114,6 +210,371 @@ impl<'de> Deserialize<'de> for FlagsmithValue {
         deserializer.deserialize_any(FlagsmithValueVisitor)
     }
 }
+
+/// Error returned when a FlagsmithValue cannot be converted to the requested type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueConversionError {
+    /// The type of the value that failed to convert.
+    pub value_type: FlagsmithValueType,
+    /// The value that failed to convert.
+    pub value: String,
+    /// The name of the requested type.
+    pub target: &'static str,
+}
+
+impl fmt::Display for ValueConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot convert {:?} value {:?} to {}",
+            self.value_type, self.value, self.target
+        )
+    }
+}
+
+impl error::Error for ValueConversionError {}
+
+/// Parses a boolean string value with optional integer conversion, ignoring case
+/// NOTE: Historical engine behavior - only "1" is treated as true, "0" is NOT treated as false
+pub(crate) fn parse_bool(s: &str, allow_int_conversion: bool) -> Option<bool> {
+    match s.to_lowercase().as_str() {
+        "true" => Some(true),
+        "1" if allow_int_conversion => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+impl FlagsmithValue {
+    /// Creates a String value
+    pub fn string(value: impl Into<String>) -> FlagsmithValue {
+        FlagsmithValue {
+            value_type: FlagsmithValueType::String,
+            value: value.into(),
+        }
+    }
+
+    /// Creates a Bool value
+    pub fn bool(value: bool) -> FlagsmithValue {
+        FlagsmithValue {
+            value_type: FlagsmithValueType::Bool,
+            value: value.to_string(),
+        }
+    }
+
+    /// Creates an Integer value
+    pub fn integer(value: i64) -> FlagsmithValue {
+        FlagsmithValue::big_integer(&BigInt::from(value))
+    }
+
+    /// Creates an Integer value from an unsigned integer
+    pub fn unsigned_integer(value: u64) -> FlagsmithValue {
+        FlagsmithValue::big_integer(&BigInt::from(value))
+    }
+
+    /// Creates an Integer value of arbitrary precision
+    ///
+    /// Integers beyond 128 bits only serialize with the `arbitrary_precision` feature.
+    pub fn big_integer(value: &BigInt) -> FlagsmithValue {
+        FlagsmithValue {
+            value_type: FlagsmithValueType::Integer,
+            value: value.to_string(),
+        }
+    }
+
+    /// Creates a Float value
+    ///
+    /// JSON has no representation for NaN and infinite floats, so they fail to serialize;
+    /// use [`FlagsmithValue::try_float`] to reject them here instead.
+    pub fn float(value: f64) -> FlagsmithValue {
+        FlagsmithValue {
+            value_type: FlagsmithValueType::Float,
+            value: value.to_string(),
+        }
+    }
+
+    /// Creates a Float value, failing for NaN and infinite floats
+    pub fn try_float(value: f64) -> Result<FlagsmithValue, ValueConversionError> {
+        let flagsmith_value = FlagsmithValue::float(value);
+        flagsmith_value.parse_float_value()?;
+        Ok(flagsmith_value)
+    }
+
+    /// Creates a Json value holding a list, e.g. for a `roles` trait
+    pub fn list<I>(items: I) -> FlagsmithValue
+    where
+        I: IntoIterator,
+        I::Item: Into<serde_json::Value>,
+    {
+        let items = items.into_iter().map(Into::into).collect();
+        FlagsmithValue::json(&serde_json::Value::Array(items))
+    }
+
+    /// Creates a Json value from a JSON object or array
+    pub(crate) fn json(value: &serde_json::Value) -> FlagsmithValue {
+        FlagsmithValue {
+            value_type: FlagsmithValueType::Json,
+            value: value.to_string(),
+        }
+    }
+
+    /// Creates an Integer or Float value from a JSON number
+    pub(crate) fn number(number: &serde_json::Number) -> FlagsmithValue {
+        FlagsmithValue {
+            value_type: if number.is_f64() {
+                FlagsmithValueType::Float
+            } else {
+                FlagsmithValueType::Integer
+            },
+            value: number.to_string(),
+        }
+    }
+
+    /// Checks that the value is valid for its type, which makes it serializable
+    pub fn validate(&self) -> Result<(), ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Bool => self.parse_bool_value().map(|_| ()),
+            FlagsmithValueType::Integer => self.parse_integer_value().map(|_| ()),
+            FlagsmithValueType::Float => self.parse_float_value().map(|_| ()),
+            FlagsmithValueType::Json => self.parse_json_value().map(|_| ()),
+            FlagsmithValueType::String | FlagsmithValueType::None => Ok(()),
+        }
+    }
+
+    /// Parses the value of a Bool
+    fn parse_bool_value(&self) -> Result<bool, ValueConversionError> {
+        self.value
+            .parse()
+            .map_err(|_| self.conversion_error("bool"))
+    }
+
+    /// Parses the value of an Integer
+    fn parse_integer_value(&self) -> Result<BigInt, ValueConversionError> {
+        self.value
+            .parse()
+            .map_err(|_| self.conversion_error("integer"))
+    }
+
+    /// Parses the value of a Float, which must be finite
+    fn parse_float_value(&self) -> Result<f64, ValueConversionError> {
+        self.value
+            .parse()
+            .ok()
+            .filter(|v: &f64| v.is_finite())
+            .ok_or_else(|| self.conversion_error("finite float"))
+    }
+
+    /// Parses the value of a Json, which must be an object or an array
+    fn parse_json_value(&self) -> Result<serde_json::Value, ValueConversionError> {
+        serde_json::from_str(&self.value)
+            .ok()
+            .filter(|v: &serde_json::Value| v.is_object() || v.is_array())
+            .ok_or_else(|| self.conversion_error("JSON"))
+    }
+
+    /// Creates a conversion error for the requested type
+    fn conversion_error(&self, target: &'static str) -> ValueConversionError {
+        ValueConversionError {
+            value_type: self.value_type.clone(),
+            value: self.value.clone(),
+            target,
+        }
+    }
+
+    /// Gets the value as a bool, parsing strings such as `"true"`
+    pub fn as_bool(&self) -> Result<bool, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Bool | FlagsmithValueType::String => parse_bool(&self.value, false),
+            _ => None,
+        }
+        .ok_or_else(|| self.conversion_error("bool"))
+    }
+
+    /// Gets the value as an i64, parsing strings such as `"10"`
+    pub fn as_i64(&self) -> Result<i64, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Integer | FlagsmithValueType::String => self.value.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| self.conversion_error("i64"))
+    }
+
+    /// Gets the value as a u64, parsing strings such as `"10"`
+    pub fn as_u64(&self) -> Result<u64, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Integer | FlagsmithValueType::String => self.value.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| self.conversion_error("u64"))
+    }
+
+    /// Gets the value as an integer of arbitrary precision, parsing strings such as `"10"`
+    pub fn as_big_integer(&self) -> Result<BigInt, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Integer | FlagsmithValueType::String => self.value.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| self.conversion_error("integer"))
+    }
+
+    /// Gets the value as an f64, widening integers and parsing strings such as `"10.5"`
+    pub fn as_f64(&self) -> Result<f64, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Float
+            | FlagsmithValueType::Integer
+            | FlagsmithValueType::String => self.value.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| self.conversion_error("f64"))
+    }
+
+    /// Gets the value of a string
+    pub fn as_str(&self) -> Result<&str, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::String => Ok(&self.value),
+            _ => Err(self.conversion_error("str")),
+        }
+    }
+
+    /// Gets the JSON object or array of a Json value
+    pub fn as_json(&self) -> Result<serde_json::Value, ValueConversionError> {
+        match self.value_type {
+            FlagsmithValueType::Json => self.parse_json_value(),
+            _ => Err(self.conversion_error("JSON")),
+        }
+    }
+
+    /// Gets the value converted to `T`, e.g. `value.get::<bool>()`
+    pub fn get<'a, T>(&'a self) -> Result<T, ValueConversionError>
+    where
+        T: TryFrom<&'a FlagsmithValue, Error = ValueConversionError>,
+    {
+        T::try_from(self)
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for bool {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        value.as_bool()
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for i64 {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        value.as_i64()
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for u64 {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        value.as_u64()
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for BigInt {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        value.as_big_integer()
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for f64 {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        value.as_f64()
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for String {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        value.as_str().map(str::to_string)
+    }
+}
+
+impl TryFrom<&FlagsmithValue> for serde_json::Value {
+    type Error = ValueConversionError;
+
+    fn try_from(value: &FlagsmithValue) -> Result<Self, Self::Error> {
+        serde_json::to_value(value).map_err(|_| value.conversion_error("JSON value"))
+    }
+}
+
+impl TryFrom<serde_json::Value> for FlagsmithValue {
+    type Error = ValueConversionError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value.clone()).map_err(|_| ValueConversionError {
+            value_type: FlagsmithValueType::Json,
+            value: value.to_string(),
+            target: "FlagsmithValue",
+        })
+    }
+}
+
+impl From<bool> for FlagsmithValue {
+    fn from(value: bool) -> Self {
+        FlagsmithValue::bool(value)
+    }
+}
+
+impl From<i64> for FlagsmithValue {
+    fn from(value: i64) -> Self {
+        FlagsmithValue::integer(value)
+    }
+}
+
+impl From<u64> for FlagsmithValue {
+    fn from(value: u64) -> Self {
+        FlagsmithValue::unsigned_integer(value)
+    }
+}
+
+impl From<BigInt> for FlagsmithValue {
+    fn from(value: BigInt) -> Self {
+        FlagsmithValue::big_integer(&value)
+    }
+}
+
+impl From<i32> for FlagsmithValue {
+    fn from(value: i32) -> Self {
+        FlagsmithValue::from(i64::from(value))
+    }
+}
+
+impl From<u32> for FlagsmithValue {
+    fn from(value: u32) -> Self {
+        FlagsmithValue::from(i64::from(value))
+    }
+}
+
+impl From<f64> for FlagsmithValue {
+    fn from(value: f64) -> Self {
+        FlagsmithValue::float(value)
+    }
+}
+
+impl From<&str> for FlagsmithValue {
+    fn from(value: &str) -> Self {
+        FlagsmithValue::from(value.to_string())
+    }
+}
+
+impl From<String> for FlagsmithValue {
+    fn from(value: String) -> Self {
+        FlagsmithValue::string(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -132,6 +593,9 @@ mod tests {
     #[case(serde_json::json!("10"), FlagsmithValueType::String)]
     #[case(serde_json::json!("true"), FlagsmithValueType::String)]
     #[case(serde_json::json!("10.1"), FlagsmithValueType::String)]
+    #[case(serde_json::json!({"plan": "pro", "limits": {"seats": 5}}), FlagsmithValueType::Json)]
+    #[case(serde_json::json!([1, "two", null]), FlagsmithValueType::Json)]
+    #[case(serde_json::json!({}), FlagsmithValueType::Json)]
     fn serialize_deserialize_flagsmith_value(
         #[case] value_as_json: serde_json::Value,
         #[case] expected_value_type: FlagsmithValueType,
@@ -145,4 +609,194 @@ mod tests {
         let serialized_value = serde_json::to_value(flagsmith_value).unwrap();
         assert_eq!(serialized_value, value_as_json);
     }
+
+    #[rstest]
+    #[case(FlagsmithValue::from(true), Ok(true))]
+    #[case(FlagsmithValue::from("False"), Ok(false))]
+    #[case(FlagsmithValue::from("1"), Err(()))]
+    #[case(FlagsmithValue::from(1), Err(()))]
+    #[case(FlagsmithValue::default(), Err(()))]
+    fn flagsmith_value_as_bool(#[case] value: FlagsmithValue, #[case] expected: Result<bool, ()>) {
+        assert_eq!(value.as_bool().map_err(|_| ()), expected);
+        assert_eq!(value.get::<bool>().map_err(|_| ()), expected);
+    }
+
+    #[rstest]
+    #[case(FlagsmithValue::from(10), Ok(10))]
+    #[case(FlagsmithValue::from("-10"), Ok(-10))]
+    #[case(FlagsmithValue::from(10.5), Err(()))]
+    #[case(FlagsmithValue::from("ten"), Err(()))]
+    fn flagsmith_value_as_i64(#[case] value: FlagsmithValue, #[case] expected: Result<i64, ()>) {
+        assert_eq!(value.as_i64().map_err(|_| ()), expected);
+        assert_eq!(value.get::<i64>().map_err(|_| ()), expected);
+    }
+
+    #[rstest]
+    #[case(FlagsmithValue::from(10.5), Ok(10.5))]
+    #[case(FlagsmithValue::from(10), Ok(10.0))]
+    #[case(FlagsmithValue::from("0.25"), Ok(0.25))]
+    #[case(FlagsmithValue::from(true), Err(()))]
+    fn flagsmith_value_as_f64(#[case] value: FlagsmithValue, #[case] expected: Result<f64, ()>) {
+        assert_eq!(value.as_f64().map_err(|_| ()), expected);
+        assert_eq!(value.get::<f64>().map_err(|_| ()), expected);
+    }
+
+    #[test]
+    fn flagsmith_value_as_str() {
+        assert_eq!(FlagsmithValue::from("text").as_str(), Ok("text"));
+        assert_eq!(
+            FlagsmithValue::from("text").get::<String>(),
+            Ok("text".to_string())
+        );
+
+        let error = FlagsmithValue::from(10).as_str().unwrap_err();
+        assert_eq!(error.value_type, FlagsmithValueType::Integer);
+        assert_eq!(error.target, "str");
+        assert_eq!(
+            error.to_string(),
+            "cannot convert Integer value \"10\" to str"
+        );
+    }
+
+    #[test]
+    fn flagsmith_value_json_conversions() {
+        let value = FlagsmithValue::try_from(serde_json::json!(10)).unwrap();
+        assert_eq!(value, FlagsmithValue::from(10));
+        assert_eq!(
+            serde_json::Value::try_from(&value).unwrap(),
+            serde_json::json!(10)
+        );
+
+        let value = FlagsmithValue::try_from(serde_json::json!({"b": [1, 2], "a": true})).unwrap();
+        assert_eq!(value.value_type, FlagsmithValueType::Json);
+        assert_eq!(value.value, r#"{"a":true,"b":[1,2]}"#);
+        assert_eq!(
+            value.as_json().unwrap(),
+            serde_json::json!({"a": true, "b": [1, 2]})
+        );
+        assert!(value.as_str().is_err());
+        assert!(FlagsmithValue::from("{}").as_json().is_err());
+        assert_eq!(
+            value.get::<serde_json::Value>(),
+            Ok(serde_json::json!({"a": true, "b": [1, 2]}))
+        );
+
+        let invalid = FlagsmithValue {
+            value_type: FlagsmithValueType::Float,
+            value: "NaN".to_string(),
+        };
+        let error = invalid.get::<serde_json::Value>().unwrap_err();
+        assert_eq!(error.value_type, FlagsmithValueType::Float);
+        assert_eq!(error.target, "JSON value");
+    }
+
+    #[rstest]
+    #[case(FlagsmithValueType::Bool, "yes")]
+    #[case(FlagsmithValueType::Integer, "abc")]
+    #[case(FlagsmithValueType::Integer, "1.5")]
+    #[case(FlagsmithValueType::Float, "NaN")]
+    #[case(FlagsmithValueType::Float, "inf")]
+    #[case(FlagsmithValueType::Json, "[")]
+    #[case(FlagsmithValueType::Json, "10")]
+    fn serialize_invalid_flagsmith_value_fails(
+        #[case] value_type: FlagsmithValueType,
+        #[case] value: &str,
+    ) {
+        let flagsmith_value = FlagsmithValue {
+            value_type,
+            value: value.to_string(),
+        };
+
+        assert!(flagsmith_value.validate().is_err());
+        assert!(serde_json::to_value(&flagsmith_value).is_err());
+        assert!(serde_json::to_string(&flagsmith_value).is_err());
+    }
+
+    #[rstest]
+    #[case(FlagsmithValue::string("text"), serde_json::json!("text"))]
+    #[case(FlagsmithValue::bool(true), serde_json::json!(true))]
+    #[case(FlagsmithValue::integer(i64::MIN), serde_json::json!(i64::MIN))]
+    #[case(FlagsmithValue::unsigned_integer(u64::MAX), serde_json::json!(u64::MAX))]
+    #[case(FlagsmithValue::float(0.5), serde_json::json!(0.5))]
+    fn flagsmith_value_constructors(
+        #[case] flagsmith_value: FlagsmithValue,
+        #[case] expected: serde_json::Value,
+    ) {
+        assert!(flagsmith_value.validate().is_ok());
+        assert_eq!(serde_json::to_value(&flagsmith_value).unwrap(), expected);
+
+        let deserialized: FlagsmithValue = serde_json::from_value(expected).unwrap();
+        assert_eq!(deserialized, flagsmith_value);
+    }
+
+    #[test]
+    fn list_creates_json_array() {
+        let value = FlagsmithValue::list(["admin", "editor"]);
+
+        assert_eq!(value.value_type, FlagsmithValueType::Json);
+        assert_eq!(
+            value.as_json().unwrap(),
+            serde_json::json!(["admin", "editor"])
+        );
+    }
+
+    #[test]
+    fn serialize_deserialize_unsigned_integer() {
+        let value: FlagsmithValue = serde_json::from_str("18446744073709551615").unwrap();
+        assert_eq!(value.value_type, FlagsmithValueType::Integer);
+        assert_eq!(value.as_u64(), Ok(u64::MAX));
+        assert!(value.as_i64().is_err());
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            "18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn serialize_128_bit_integer() {
+        let value = FlagsmithValue::big_integer(&BigInt::from(i128::MIN));
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            i128::MIN.to_string()
+        );
+        assert_eq!(value.as_big_integer(), Ok(BigInt::from(i128::MIN)));
+    }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    #[test]
+    fn serialize_integer_beyond_128_bits_fails() {
+        let value = FlagsmithValue::big_integer(&(BigInt::from(u128::MAX) * 10));
+        assert!(value.validate().is_ok());
+        assert!(serde_json::to_string(&value).is_err());
+    }
+
+    #[rstest]
+    #[case(f64::NAN)]
+    #[case(f64::INFINITY)]
+    #[case(f64::NEG_INFINITY)]
+    fn try_float_rejects_non_finite_values(#[case] value: f64) {
+        let error = FlagsmithValue::try_float(value).unwrap_err();
+        assert_eq!(error.value_type, FlagsmithValueType::Float);
+        assert_eq!(error.target, "finite float");
+    }
+
+    #[test]
+    fn try_float_accepts_finite_values() {
+        assert_eq!(
+            FlagsmithValue::try_float(1.5),
+            Ok(FlagsmithValue::float(1.5))
+        );
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn serialize_deserialize_integer_beyond_128_bits() {
+        let digits = "3402823669209384634633746074317682114550";
+        let value: FlagsmithValue = serde_json::from_str(digits).unwrap();
+        assert_eq!(value, FlagsmithValue::big_integer(&digits.parse().unwrap()));
+        assert_eq!(serde_json::to_string(&value).unwrap(), digits);
+
+        let value: FlagsmithValue = serde_json::from_str("1.5").unwrap();
+        assert_eq!(value, FlagsmithValue::float(1.5));
+    }
 }
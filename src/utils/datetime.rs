@@ -1,5 +1,6 @@
 // A module that helps serialize/deserialize datetime that may or may not have timezone using the rfc3339 format
 // NOTE: Datetime that don't have timezone info are assumed to be in UTC
+use crate::error::Error;
 use chrono::{DateTime, Utc};
 use serde::{self, Deserialize, Deserializer, Serializer};
 
@@ -10,25 +11,52 @@ where
     serializer.serialize_str(&date.to_rfc3339())
 }
 
-pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    let datetime = match DateTime::parse_from_rfc3339(&s) {
+/// Parses an rfc3339 datetime, assuming UTC when it has no timezone
+pub fn parse(s: &str) -> Result<DateTime<Utc>, Error> {
+    let datetime = match DateTime::parse_from_rfc3339(s) {
         Ok(datetime) => datetime,
         // TODO: pattern match on ErrorKind once it's public
         // ref: https://github.com/chronotope/chrono/issues/319
         Err(error) => match error.to_string().as_str() {
-            "premature end of input" => {
-                let datetime_str = s + "Z";
-                DateTime::parse_from_rfc3339(&datetime_str).map_err(serde::de::Error::custom)?
-            }
-            _ => panic!("Invalid datetime"),
+            "premature end of input" => DateTime::parse_from_rfc3339(&format!("{}Z", s))?,
+            _ => return Err(error.into()),
         },
     };
-    let datatime_utc = datetime.with_timezone(&Utc);
-    return Ok(datatime_utc);
+    Ok(datetime.with_timezone(&Utc))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse(&s).map_err(serde::de::Error::custom)
+}
+
+/// Serializes/deserializes optional datetimes the same way, e.g. with
+/// `#[serde(default, with = "utils::datetime::option")]`
+pub mod option {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(date) => super::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| super::parse(&s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
 }
 
 #[cfg(test)]
@@ -64,4 +92,54 @@ mod tests {
             serde_json::json!({"datetime":"2021-11-29T17:15:51.694223+00:00"})
         );
     }
+
+    #[rstest]
+    #[case("not a datetime")]
+    #[case("2021-13-29T17:15:51Z")]
+    #[case("2021-11-29")]
+    fn parse_invalid_datetime_returns_error(#[case] given_datetime: &str) {
+        let error = parse(given_datetime).unwrap_err();
+
+        assert_eq!(error.kind, crate::error::ErrorKind::InvalidDatetime);
+    }
+
+    #[rstest]
+    #[case(serde_json::json!({"datetime": "2021-11-29T17:15:51"}), Some("2021-11-29T17:15:51+00:00"))]
+    #[case(serde_json::json!({"datetime": null}), None)]
+    #[case(serde_json::json!({}), None)]
+    fn serialize_deserialize_optional_datetime(
+        #[case] given_datetime: serde_json::Value,
+        #[case] expected: Option<&str>,
+    ) {
+        #[derive(Serialize, Deserialize)]
+        struct AStruct {
+            #[serde(default, with = "utils::datetime::option")]
+            datetime: Option<DateTime<Utc>>,
+        }
+
+        let deserialized_struct: AStruct = serde_json::from_value(given_datetime).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(deserialized_struct).unwrap(),
+            serde_json::json!({ "datetime": expected })
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_datetime_returns_error() {
+        #[derive(Debug, Deserialize)]
+        struct AStruct {
+            #[serde(with = "utils::datetime")]
+            #[allow(dead_code)]
+            datetime: DateTime<Utc>,
+        }
+
+        let result: Result<AStruct, _> =
+            serde_json::from_value(serde_json::json!({"datetime": "not a datetime"}));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid datetime"));
+    }
 }
@@ -1,5 +1,9 @@
-use flagsmith_flag_engine::engine::get_evaluation_result;
-use flagsmith_flag_engine::engine_eval::{EngineEvaluationContext, EvaluationResult};
+use flagsmith_flag_engine::engine::{
+    get_evaluation_result, get_evaluation_result_for_identity, get_flag_result,
+};
+use flagsmith_flag_engine::engine_eval::{
+    CompiledContext, EngineEvaluationContext, EvaluationResult,
+};
 use json_comments::StripComments;
 use rstest::*;
 use serde_json;
@@ -73,5 +77,46 @@ fn test_engine_evaluation() {
             "Segments mismatch in {}",
             test_name
         );
+
+        // Single feature evaluation must agree with the full evaluation
+        for (feature_name, flag) in &result.flags {
+            assert_eq!(
+                get_flag_result(&context, feature_name).as_ref(),
+                Some(flag),
+                "Flag {} mismatch in {}",
+                feature_name,
+                test_name
+            );
+        }
+
+        // Evaluating the identity apart from the context must agree as well
+        if let Some(identity) = &context.identity {
+            let mut anonymous_context = context.clone();
+            anonymous_context.identity = None;
+            assert_eq!(
+                get_evaluation_result_for_identity(&anonymous_context, identity),
+                result,
+                "Borrowed identity result mismatch in {}",
+                test_name
+            );
+        }
+
+        // The compiled context must evaluate identically
+        let compiled = CompiledContext::new(context);
+        assert_eq!(
+            compiled.get_evaluation_result(),
+            result,
+            "Compiled result mismatch in {}",
+            test_name
+        );
+        for (feature_name, flag) in &result.flags {
+            assert_eq!(
+                compiled.get_flag_result(feature_name).as_ref(),
+                Some(flag),
+                "Compiled flag {} mismatch in {}",
+                feature_name,
+                test_name
+            );
+        }
     }
 }
@@ -1,8 +1,13 @@
 use flagsmith_flag_engine::engine_eval::{
+    add_identity_to_context,
     context::{ConditionOperator, ConditionValue, SegmentRuleType, SegmentSource},
-    environment_to_context,
+    environment_to_context, identity_to_context, try_environment_to_context,
 };
 use flagsmith_flag_engine::environments::Environment;
+use flagsmith_flag_engine::error::ErrorKind;
+use flagsmith_flag_engine::identities::Trait;
+use flagsmith_flag_engine::types::{FlagsmithValue, FlagsmithValueType};
+use rstest::*;
 
 fn get_environment_fixture() -> &'static str {
     r#"{
@@ -172,6 +177,9 @@ fn test_environment_to_context_produces_evaluation_context() {
     // Verify environment context
     assert_eq!(context.environment.key, "test_key");
     assert_eq!(context.environment.name, "Test Environment");
+    assert!(!context.environment.hide_disabled_flags);
+    assert!(!context.environment.stop_serving_flags);
+    assert!(!context.environment.feature_analytics);
     assert!(context.identity.is_none());
     assert_eq!(context.segments.len(), 2);
 
@@ -309,3 +317,110 @@ fn test_environment_to_context_produces_evaluation_context() {
     assert_eq!(mv_feature_without_ids.variants[2].value.value, "option_z");
     assert_eq!(mv_feature_without_ids.variants[2].weight, 25.0);
 }
+
+#[test]
+fn test_environment_to_context_carries_organisation_settings() {
+    let json = get_environment_fixture();
+    let mut environment: Environment =
+        serde_json::from_str(json).expect("Failed to parse environment");
+    environment.project.organisation.feature_analytics = true;
+    environment.project.organisation.stop_serving_flags = true;
+
+    let context = environment_to_context(environment);
+
+    assert!(context.environment.feature_analytics);
+    assert!(context.environment.stop_serving_flags);
+}
+
+#[test]
+fn test_identity_to_context_maps_identity_without_copying_context() {
+    let json = get_environment_fixture();
+    let environment: Environment = serde_json::from_str(json).expect("Failed to parse environment");
+    let context = environment_to_context(environment);
+    let traits = vec![Trait {
+        trait_key: "plan".to_string(),
+        trait_value: FlagsmithValue {
+            value: "pro".to_string(),
+            value_type: FlagsmithValueType::String,
+        },
+    }];
+
+    let identity = identity_to_context(&context, "user", &traits);
+
+    assert_eq!(identity.identifier, "user");
+    assert_eq!(identity.key, "test_key_user");
+    assert_eq!(identity.traits["plan"].value, "pro");
+
+    let identity_context = add_identity_to_context(&context, "user", &traits);
+    let added_identity = identity_context.identity.unwrap();
+    assert_eq!(added_identity.key, identity.key);
+    assert_eq!(added_identity.traits["plan"].value, "pro");
+}
+
+#[test]
+fn test_try_environment_to_context_maps_known_operators() {
+    let json = get_environment_fixture();
+    let environment: Environment = serde_json::from_str(json).expect("Failed to parse environment");
+
+    let context = try_environment_to_context(environment.clone()).unwrap();
+
+    assert_eq!(
+        context.segments.len(),
+        environment_to_context(environment).segments.len()
+    );
+}
+
+#[test]
+fn test_try_environment_to_context_fails_on_unknown_operator() {
+    let json = get_environment_fixture();
+    let mut environment: Environment =
+        serde_json::from_str(json).expect("Failed to parse environment");
+    environment.project.segments[0].rules[0].rules[0].conditions[0].operator =
+        "SOUNDS_LIKE".to_string();
+
+    let error = try_environment_to_context(environment.clone()).unwrap_err();
+
+    assert_eq!(error.kind, ErrorKind::Mapping);
+    assert_eq!(
+        error.to_string(),
+        r#"Mapping to evaluation context failed: segment 1: unknown operator "SOUNDS_LIKE""#
+    );
+
+    // The lenient mapping still falls back to EQUAL
+    let context = environment_to_context(environment);
+    let condition = &context.segments["1"].rules[0].rules[0].conditions[0];
+    assert_eq!(condition.operator, ConditionOperator::Equal);
+}
+
+#[rstest]
+#[case("IN_SEGMENT", ConditionOperator::InSegment)]
+#[case("STARTS_WITH", ConditionOperator::StartsWith)]
+#[case("ENDS_WITH", ConditionOperator::EndsWith)]
+#[case("NOT_IN", ConditionOperator::NotIn)]
+#[case("EQUAL_IGNORE_CASE", ConditionOperator::EqualIgnoreCase)]
+#[case("CONTAINS_IGNORE_CASE", ConditionOperator::ContainsIgnoreCase)]
+#[case("IN_IGNORE_CASE", ConditionOperator::InIgnoreCase)]
+#[case("SEMVER_RANGE", ConditionOperator::SemverRange)]
+#[case("IN_CIDR", ConditionOperator::InCidr)]
+#[case("CONTAINS_ANY", ConditionOperator::ContainsAny)]
+#[case("CONTAINS_ALL", ConditionOperator::ContainsAll)]
+#[case("SIZE_GREATER_THAN", ConditionOperator::SizeGreaterThan)]
+fn test_try_environment_to_context_maps_added_operators(
+    #[case] operator: &str,
+    #[case] expected: ConditionOperator,
+) {
+    let json = get_environment_fixture();
+    let mut environment: Environment =
+        serde_json::from_str(json).expect("Failed to parse environment");
+    environment.project.segments[0].rules[0].rules[0].conditions[0].operator = operator.to_string();
+
+    let context = try_environment_to_context(environment.clone()).unwrap();
+
+    let condition = &context.segments["1"].rules[0].rules[0].conditions[0];
+    assert_eq!(condition.operator, expected);
+
+    // The lenient mapping does not fall back to EQUAL either
+    let context = environment_to_context(environment);
+    let condition = &context.segments["1"].rules[0].rules[0].conditions[0];
+    assert_eq!(condition.operator, expected);
+}